@@ -0,0 +1,192 @@
+//! Compares the packed bit fields and the bit_fields_by_day index with the previous representation
+//! (one byte per bit and one FxHashSet per day).
+//!
+//! Run with `cargo bench --bench bit_fields`. The serialization benchmarks report the size of the serialized data
+//! (i.e. the cache) as their throughput.
+use std::hint::black_box;
+
+use bincode::config;
+use chrono::{Days, NaiveDate};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use hrdf_parser::{BIT_FIELD_WORDS, BitField, BitFieldsByDay};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+const NUM_BIT_FIELDS: usize = 30_000;
+const NUM_DAYS: usize = 380;
+
+fn start_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 12, 15).unwrap()
+}
+
+fn end_date() -> NaiveDate {
+    start_date()
+        .checked_add_days(Days::new(NUM_DAYS as u64 - 1))
+        .unwrap()
+}
+
+/// Deterministic pseudo-random bit fields (xorshift).
+fn create_words() -> Vec<[u64; BIT_FIELD_WORDS]> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    (0..NUM_BIT_FIELDS)
+        .map(|_| {
+            std::array::from_fn(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            })
+        })
+        .collect()
+}
+
+fn create_bit_fields(words: &[[u64; BIT_FIELD_WORDS]]) -> Vec<BitField> {
+    words
+        .iter()
+        .enumerate()
+        .map(|(i, &words)| BitField::new(i as i32 + 1, words))
+        .collect()
+}
+
+/// The previous representation: one byte per bit.
+fn create_legacy_bit_fields(words: &[[u64; BIT_FIELD_WORDS]]) -> Vec<(i32, Vec<u8>)> {
+    words
+        .iter()
+        .enumerate()
+        .map(|(i, words)| {
+            let bits = (0..BIT_FIELD_WORDS * 64)
+                .map(|bit| ((words[bit / 64] >> (bit % 64)) & 1) as u8)
+                .collect();
+            (i as i32 + 1, bits)
+        })
+        .collect()
+}
+
+/// The previous index: one FxHashSet per day.
+fn create_legacy_bit_fields_by_day(
+    bit_fields: &[(i32, Vec<u8>)],
+) -> FxHashMap<NaiveDate, FxHashSet<i32>> {
+    let dates: Vec<_> = start_date().iter_days().take(NUM_DAYS).collect();
+    let mut map: FxHashMap<NaiveDate, FxHashSet<i32>> = dates
+        .iter()
+        .map(|&date| (date, FxHashSet::from_iter([0])))
+        .collect();
+
+    for (id, bits) in bit_fields {
+        bits.iter()
+            .skip(2)
+            .enumerate()
+            .filter(|&(i, &bit)| i < NUM_DAYS && bit == 1)
+            .for_each(|(i, _)| {
+                map.entry(dates[i]).or_default().insert(*id);
+            });
+    }
+    map
+}
+
+fn serialized_size<T: serde::Serialize>(value: &T) -> u64 {
+    bincode::serde::encode_to_vec(value, config::standard())
+        .unwrap()
+        .len() as u64
+}
+
+fn bench_bit_fields(c: &mut Criterion) {
+    let words = create_words();
+    let bit_fields = create_bit_fields(&words);
+    let legacy_bit_fields = create_legacy_bit_fields(&words);
+    let bit_fields_by_day =
+        BitFieldsByDay::new(bit_fields.iter().collect(), start_date(), end_date());
+    let legacy_bit_fields_by_day = create_legacy_bit_fields_by_day(&legacy_bit_fields);
+
+    // The throughput is computed from the size of the serialized data, so criterion reports both.
+    let mut group = c.benchmark_group("bit_fields_serialize");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(serialized_size(&bit_fields)));
+    group.bench_function("packed", |b| {
+        b.iter(|| serialized_size(black_box(&bit_fields)))
+    });
+    group.throughput(Throughput::Bytes(serialized_size(&legacy_bit_fields)));
+    group.bench_function("legacy", |b| {
+        b.iter(|| serialized_size(black_box(&legacy_bit_fields)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("bit_fields_by_day_serialize");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(serialized_size(&bit_fields_by_day)));
+    group.bench_function("packed", |b| {
+        b.iter(|| serialized_size(black_box(&bit_fields_by_day)))
+    });
+    group.throughput(Throughput::Bytes(serialized_size(
+        &legacy_bit_fields_by_day,
+    )));
+    group.bench_function("legacy", |b| {
+        b.iter(|| serialized_size(black_box(&legacy_bit_fields_by_day)))
+    });
+    group.finish();
+
+    let queries: Vec<(usize, usize)> = (0..10_000)
+        .map(|i| ((i * 7919) % NUM_BIT_FIELDS, (i * 104_729) % NUM_DAYS))
+        .collect();
+
+    let mut group = c.benchmark_group("bit_field_contains");
+    group.bench_function("packed", |b| {
+        b.iter(|| {
+            queries
+                .iter()
+                .filter(|&&(i, day)| bit_fields[i].contains(day))
+                .count()
+        })
+    });
+    group.bench_function("legacy", |b| {
+        b.iter(|| {
+            queries
+                .iter()
+                .filter(|&&(i, day)| legacy_bit_fields[i].1.get(day + 2) == Some(&1))
+                .count()
+        })
+    });
+    group.finish();
+
+    let dates: Vec<_> = start_date().iter_days().take(NUM_DAYS).collect();
+    let mut group = c.benchmark_group("bit_fields_by_day_contains");
+    group.bench_function("packed", |b| {
+        b.iter(|| {
+            queries
+                .iter()
+                .filter(|&&(i, day)| bit_fields_by_day.contains(dates[day], i as i32 + 1))
+                .count()
+        })
+    });
+    group.bench_function("legacy", |b| {
+        b.iter(|| {
+            queries
+                .iter()
+                .filter(|&&(i, day)| {
+                    legacy_bit_fields_by_day
+                        .get(&dates[day])
+                        .is_some_and(|ids| ids.contains(&(i as i32 + 1)))
+                })
+                .count()
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("bit_fields_by_day_build");
+    group.sample_size(10);
+    group.bench_function("packed", |b| {
+        b.iter(|| {
+            BitFieldsByDay::new(
+                black_box(bit_fields.iter().collect()),
+                start_date(),
+                end_date(),
+            )
+        })
+    });
+    group.bench_function("legacy", |b| {
+        b.iter(|| create_legacy_bit_fields_by_day(black_box(&legacy_bit_fields)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_bit_fields);
+criterion_main!(benches);
@@ -0,0 +1,406 @@
+// ------------------------------------------------------------------------------------------------
+// --- Departure and arrival boards
+// ------------------------------------------------------------------------------------------------
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    models::{Journey, Model, Platform, TransportType},
+    storage::DataStorage,
+    utils::sub_1_day,
+};
+
+/// A departure or an arrival of a journey at a stop.
+#[derive(Debug)]
+pub struct BoardEntry<'a> {
+    journey: &'a Journey,
+    route_index: usize,      // Position of the stop in the journey's route.
+    service_date: NaiveDate, // Date of the route's first entry.
+    time: NaiveDateTime,
+    line: Option<&'a str>,
+    direction: &'a str,
+    transport_type: &'a TransportType,
+    platform: Option<&'a Platform>,
+}
+
+impl<'a> BoardEntry<'a> {
+    // Getters/Setters
+
+    pub fn journey(&self) -> &'a Journey {
+        self.journey
+    }
+
+    pub fn route_index(&self) -> usize {
+        self.route_index
+    }
+
+    pub fn service_date(&self) -> NaiveDate {
+        self.service_date
+    }
+
+    pub fn time(&self) -> NaiveDateTime {
+        self.time
+    }
+
+    pub fn line(&self) -> Option<&'a str> {
+        self.line
+    }
+
+    /// The direction text of the journey, or the name of its last stop if there is none.
+    /// If the journey continues into other journeys (see DataStorage::vehicle_run), the direction of the last one is used.
+    pub fn direction(&self) -> &'a str {
+        self.direction
+    }
+
+    pub fn transport_type(&self) -> &'a TransportType {
+        self.transport_type
+    }
+
+    /// The planned platform (GLEIS file), if any.
+    pub fn platform(&self) -> Option<&'a Platform> {
+        self.platform
+    }
+
+    // Functions
+
+    /// Returns true if the journey started on the previous day, i.e. the time is after midnight of its service date.
+    pub fn is_next_day(&self) -> bool {
+        self.time.date() > self.service_date
+    }
+}
+
+impl DataStorage {
+    /// Returns the departures from the stop between from (included) and from + window (excluded), sorted by time.
+    /// The stops at which boarding is not allowed are not included.
+    /// The journeys which started on the previous day and depart after midnight are included.
+    pub fn departures(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        window: TimeDelta,
+    ) -> Vec<BoardEntry<'_>> {
+        create_board(self, stop_id, from, window, true)
+    }
+
+    /// Returns the arrivals at the stop between from (included) and from + window (excluded), sorted by time.
+    /// The stops at which alighting is not allowed are not included.
+    /// The journeys which started on the previous day and arrive after midnight are included.
+    pub fn arrivals(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        window: TimeDelta,
+    ) -> Vec<BoardEntry<'_>> {
+        create_board(self, stop_id, from, window, false)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+fn create_board(
+    data_storage: &DataStorage,
+    stop_id: i32,
+    from: NaiveDateTime,
+    window: TimeDelta,
+    is_departure: bool,
+) -> Vec<BoardEntry<'_>> {
+    let until = from + window;
+    let mut entries = Vec::new();
+
+    // The journeys of the previous service day can still serve the stop after midnight.
+    for service_date in sub_1_day(from.date())
+        .iter_days()
+        .take_while(|&date| date <= until.date())
+    {
+        for journey in journeys_serving_stop(data_storage, stop_id, service_date) {
+            // unwrap: The first route entry always has a departure time.
+            let origin =
+                NaiveDateTime::new(service_date, journey.route()[0].departure_time().unwrap());
+            let offsets = journey.stop_offsets();
+            // The passengers can stay on board until the end of the vehicle run. The run is only followed
+            // once an entry of the journey falls within the window, and at most once per journey.
+            let find_run_destination = || {
+                data_storage
+                    .through_services_by_journey_id()
+                    .contains_key(&journey.id())
+                    .then(|| data_storage.vehicle_run(journey, service_date))
+                    .filter(|run| run.journeys().len() > 1)
+                    .map(|run| {
+                        // unwrap: A run always contains at least 1 journey.
+                        let &(last_journey, _) = run.journeys().last().unwrap();
+                        last_journey
+                            .direction_at(last_journey.route().len() - 1, data_storage)
+                            .unwrap_or_else(|| last_stop_name(last_journey, data_storage))
+                    })
+            };
+            let mut run_destination = None;
+
+            for (route_index, route_entry) in journey.route().iter().enumerate() {
+                if route_entry.stop_id() != stop_id {
+                    continue;
+                }
+
+                let (is_allowed, offset) = if is_departure {
+                    (route_entry.boarding_allowed(), offsets[route_index].1)
+                } else {
+                    (route_entry.alighting_allowed(), offsets[route_index].0)
+                };
+                let Some(offset) = offset.filter(|_| is_allowed) else {
+                    continue;
+                };
+
+                let time_at_stop = origin + TimeDelta::minutes(offset.into());
+                if time_at_stop < from || time_at_stop >= until {
+                    continue;
+                }
+
+                entries.push(BoardEntry {
+                    journey,
+                    route_index,
+                    service_date,
+                    time: time_at_stop,
+                    line: journey.line_designation(data_storage),
+                    direction: run_destination
+                        .get_or_insert_with(find_run_destination)
+                        .unwrap_or_else(|| {
+                            journey
+                                .direction_at(route_index, data_storage)
+                                .unwrap_or_else(|| last_stop_name(journey, data_storage))
+                        }),
+                    transport_type: journey.transport_type(data_storage),
+                    platform: journey.platform_at(route_index, service_date, data_storage),
+                });
+            }
+        }
+    }
+
+    entries.sort_by_key(|entry| (entry.time, entry.journey.id(), entry.route_index));
+    entries
+}
+
+/// Name of the last stop of the journey's route.
+fn last_stop_name<'a>(journey: &'a Journey, data_storage: &'a DataStorage) -> &'a str {
+    // unwrap: A route always contains at least 2 entries.
+    journey.route().last().unwrap().stop(data_storage).name()
+}
+
+/// Returns the journeys operating on the date and serving the stop on that date.
+pub(crate) fn journeys_serving_stop(
+    data_storage: &DataStorage,
+    stop_id: i32,
+    date: NaiveDate,
+) -> Vec<&Journey> {
+    let journey_ids: FxHashSet<i32> = data_storage
+        .bit_fields_by_stop_id()
+        .get(&stop_id)
+        .into_iter()
+        .flatten()
+        .filter(|&&bit_field_id| {
+            data_storage
+                .bit_fields_by_day()
+                .contains(date, bit_field_id)
+        })
+        .filter_map(|&bit_field_id| {
+            data_storage
+                .journeys_by_stop_id_and_bit_field_id()
+                .get(&(stop_id, bit_field_id))
+        })
+        .flatten()
+        .copied()
+        .collect();
+
+    journey_ids
+        .into_iter()
+        .filter_map(|journey_id| data_storage.journeys().find(journey_id))
+        // The stop can be served only on some days (*SH lines).
+        .filter(|journey| journey.serves_stop_on(stop_id, date, data_storage))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        routing::tests::{data_storage, date_time},
+        storage::tests::data_storage_from,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// - IC 1: A 08:00, B 08:30/08:32 (neither boarding nor alighting), C 09:00
+    /// - B 2: A 08:00, C 08:40
+    /// - B 3: A 07:30, B 07:50
+    /// - B 4: A 23:50, B 00:20/00:22, C 00:40 (after midnight), only on 03.03.2025
+    /// - B 5: A 08:30, C 09:10
+    /// - B 6: A 09:00, B 09:30/09:32 (no alighting), C 10:00
+    fn board_fixture() -> DataStorage {
+        data_storage_from(&[
+            (
+                "BAHNHOF",
+                &[
+                    "8500010     A$<1>",
+                    "8500020     B$<1>",
+                    "8500030     C$<1>",
+                ],
+            ),
+            (
+                "BITFELD",
+                &[
+                    "000001 000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000",
+                ],
+            ),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G IC  8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000000                               %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                    -00830 -00832                 %",
+                    "8500030 C                     00900                        %",
+                    "*Z 000002 000011                                           %",
+                    "*G B   8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000000                               %",
+                    "8500010 A                            00800                 %",
+                    "8500030 C                     00840                        %",
+                    "*Z 000003 000011                                           %",
+                    "*G B   8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "8500010 A                            00730                 %",
+                    "8500020 B                     00750                        %",
+                    "*Z 000004 000011                                           %",
+                    "*G B   8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000001                               %",
+                    "8500010 A                            02350                 %",
+                    "8500020 B                     02420  02422                 %",
+                    "8500030 C                     02440                        %",
+                    "*Z 000005 000011                                           %",
+                    "*G B   8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000000                               %",
+                    "8500010 A                            00830                 %",
+                    "8500030 C                     00910                        %",
+                    "*Z 000006 000011                                           %",
+                    "*G B   8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000000                               %",
+                    "8500010 A                            00900                 %",
+                    "8500020 B                    -00930  00932                 %",
+                    "8500030 C                     01000                        %",
+                ],
+            ),
+        ])
+    }
+
+    fn describe(entries: &[BoardEntry]) -> Vec<(i32, String)> {
+        entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.journey().id(),
+                    entry.time().format("%d %H:%M").to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sort_order_and_time_window_v207() {
+        let data_storage = board_fixture();
+        let from = date_time("2025-03-03", "07:30");
+
+        // The start of the window is included, its end excluded. The ties are sorted by journey.
+        assert_eq!(
+            vec![
+                (3, "03 07:30".to_string()),
+                (1, "03 08:00".to_string()),
+                (2, "03 08:00".to_string()),
+            ],
+            describe(&data_storage.departures(8500010, from, TimeDelta::hours(1)))
+        );
+        assert_eq!(
+            vec![(2, "03 08:40".to_string()), (1, "03 09:00".to_string())],
+            describe(&data_storage.arrivals(
+                8500030,
+                date_time("2025-03-03", "08:40"),
+                TimeDelta::minutes(21)
+            ))
+        );
+    }
+
+    #[test]
+    fn previous_service_day_after_midnight_v207() {
+        let data_storage = board_fixture();
+
+        // The journey 4 only operates on 03.03.2025, it reaches B and C after midnight.
+        let from = date_time("2025-03-04", "00:00");
+        let departures = data_storage.departures(8500020, from, TimeDelta::hours(1));
+        assert_eq!(vec![(4, "04 00:22".to_string())], describe(&departures));
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+            departures[0].service_date()
+        );
+        assert!(departures[0].is_next_day());
+        assert_eq!(
+            vec![(4, "04 00:40".to_string())],
+            describe(&data_storage.arrivals(8500030, from, TimeDelta::hours(1)))
+        );
+
+        // The same journey before midnight.
+        let departures = data_storage.departures(
+            8500010,
+            date_time("2025-03-03", "23:00"),
+            TimeDelta::hours(1),
+        );
+        assert_eq!(vec![(4, "03 23:50".to_string())], describe(&departures));
+        assert!(!departures[0].is_next_day());
+
+        // Not on the other days.
+        let from = date_time("2025-03-05", "00:00");
+        assert!(
+            data_storage
+                .departures(8500020, from, TimeDelta::hours(1))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn boarding_and_alighting_v207() {
+        let data_storage = board_fixture();
+        let from = date_time("2025-03-03", "07:00");
+
+        // The journey 1 neither boards nor alights at B, the journey 6 only boards.
+        assert_eq!(
+            vec![(6, "03 09:32".to_string())],
+            describe(&data_storage.departures(8500020, from, TimeDelta::hours(3)))
+        );
+        assert_eq!(
+            vec![(3, "03 07:50".to_string())],
+            describe(&data_storage.arrivals(8500020, from, TimeDelta::hours(3)))
+        );
+        let departures = data_storage.departures(8500020, from, TimeDelta::hours(3));
+        assert_eq!(1, departures[0].route_index());
+    }
+
+    #[test]
+    fn through_service_direction_v207() {
+        let data_storage = data_storage();
+        let from = date_time("2025-03-03", "11:00");
+
+        // The journey 10 continues into the journey 11, the end of the run is shown.
+        let departures = data_storage.departures(8500140, from, TimeDelta::hours(2));
+        assert_eq!(1, departures.len());
+        assert_eq!(10, departures[0].journey().id());
+        assert_eq!("P", departures[0].direction());
+
+        // A journey without through service shows its own last stop.
+        let departures = data_storage.departures(8500150, from, TimeDelta::hours(2));
+        assert_eq!(1, departures.len());
+        assert_eq!(11, departures[0].journey().id());
+        assert_eq!("P", departures[0].direction());
+        let from = date_time("2025-03-03", "09:55");
+        let departures = data_storage.departures(8500110, from, TimeDelta::minutes(10));
+        assert_eq!(1, departures.len());
+        assert_eq!("M", departures[0].direction());
+    }
+}
@@ -0,0 +1,339 @@
+// ------------------------------------------------------------------------------------------------
+// --- Direct connections
+// ------------------------------------------------------------------------------------------------
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+
+use crate::{
+    board::journeys_serving_stop,
+    models::{Journey, JourneyRouteEntry, Model},
+    storage::DataStorage,
+    utils::sub_1_day,
+};
+
+/// A ride without transfer between two stops.
+#[derive(Debug)]
+pub struct DirectConnection<'a> {
+    journey: &'a Journey,
+    service_date: NaiveDate, // Date of the route's first entry.
+    departure_index: usize,  // Position of the departure stop in the journey's route.
+    arrival_index: usize,    // Position of the arrival stop in the journey's route.
+    departure_time: NaiveDateTime,
+    arrival_time: NaiveDateTime,
+}
+
+impl<'a> DirectConnection<'a> {
+    // Getters/Setters
+
+    pub fn journey(&self) -> &'a Journey {
+        self.journey
+    }
+
+    pub fn service_date(&self) -> NaiveDate {
+        self.service_date
+    }
+
+    pub fn departure_index(&self) -> usize {
+        self.departure_index
+    }
+
+    pub fn arrival_index(&self) -> usize {
+        self.arrival_index
+    }
+
+    pub fn departure_time(&self) -> NaiveDateTime {
+        self.departure_time
+    }
+
+    pub fn arrival_time(&self) -> NaiveDateTime {
+        self.arrival_time
+    }
+
+    // Functions
+
+    /// Number of stops between the departure and the arrival stops, both excluded.
+    pub fn count_stops(&self) -> usize {
+        self.arrival_index - self.departure_index - 1
+    }
+
+    /// Route entries from the departure to the arrival stop, both included.
+    pub fn route_section(&self) -> &'a [JourneyRouteEntry] {
+        &self.journey.route()[self.departure_index..=self.arrival_index]
+    }
+
+    pub fn duration(&self) -> TimeDelta {
+        self.arrival_time - self.departure_time
+    }
+}
+
+impl DataStorage {
+    /// Returns the journeys going from a stop to another without transfer, departing between from (included)
+    /// and from + window (excluded), sorted by departure time.
+    /// Boarding must be allowed at the departure stop, alighting at the arrival stop, and both stops must be served
+    /// on the service date (*SH lines). The journeys which started on the previous day and depart after midnight are included.
+    /// If a journey serves the departure stop several times before the arrival stop, the last boarding is used.
+    pub fn direct_connections(
+        &self,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        from: NaiveDateTime,
+        window: TimeDelta,
+    ) -> Vec<DirectConnection<'_>> {
+        let until = from + window;
+        let mut connections = Vec::new();
+
+        // The journeys of the previous service day can still serve the stop after midnight.
+        for service_date in sub_1_day(from.date())
+            .iter_days()
+            .take_while(|&date| date <= until.date())
+        {
+            for journey in journeys_serving_stop(self, departure_stop_id, service_date) {
+                if !journey.serves_stop_on(arrival_stop_id, service_date, self) {
+                    continue;
+                }
+
+                // unwrap: The first route entry always has a departure time.
+                let origin =
+                    NaiveDateTime::new(service_date, journey.route()[0].departure_time().unwrap());
+                let offsets = journey.stop_offsets();
+                let time_at = |offset: i32| origin + TimeDelta::minutes(offset.into());
+
+                for (departure_index, arrival_index) in
+                    sections(journey, departure_stop_id, arrival_stop_id)
+                {
+                    let (Some(departure_offset), Some(arrival_offset)) =
+                        (offsets[departure_index].1, offsets[arrival_index].0)
+                    else {
+                        continue;
+                    };
+
+                    let departure_time = time_at(departure_offset);
+                    if departure_time < from || departure_time >= until {
+                        continue;
+                    }
+
+                    connections.push(DirectConnection {
+                        journey,
+                        service_date,
+                        departure_index,
+                        arrival_index,
+                        departure_time,
+                        arrival_time: time_at(arrival_offset),
+                    });
+                }
+            }
+        }
+
+        connections.sort_by_key(|connection| {
+            (
+                connection.departure_time,
+                connection.arrival_time,
+                connection.journey.id(),
+                connection.departure_index,
+            )
+        });
+        connections
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Returns the (departure_index, arrival_index) pairs of the route going from a stop to the other.
+/// A route can serve the same stop several times (e.g. a loop): each arrival is paired with the last boarding before it
+/// and each boarding with the first alighting after it.
+fn sections(
+    journey: &Journey,
+    departure_stop_id: i32,
+    arrival_stop_id: i32,
+) -> Vec<(usize, usize)> {
+    let mut sections = Vec::new();
+    let mut departure_index = None;
+
+    for (index, route_entry) in journey.route().iter().enumerate() {
+        if route_entry.stop_id() == arrival_stop_id
+            && route_entry.alighting_allowed()
+            && let Some(departure_index) = departure_index.take()
+        {
+            sections.push((departure_index, index));
+        }
+        if route_entry.stop_id() == departure_stop_id && route_entry.boarding_allowed() {
+            departure_index = Some(index);
+        }
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routing::tests::date_time, storage::tests::data_storage_from};
+    use pretty_assertions::assert_eq;
+
+    /// - B 1: A 08:00, B 08:10/08:11, C 08:20/08:21, D 08:30
+    /// - B 2: A 09:00, B 09:10/09:11, A 09:20/09:21, C 09:30 (loop)
+    /// - B 3: H 23:30, I 00:10/00:12, J 00:40 (after midnight), only on 03.03.2025
+    fn direct_connection_fixture() -> DataStorage {
+        data_storage_from(&[
+            (
+                "BAHNHOF",
+                &[
+                    "8500010     A$<1>",
+                    "8500020     B$<1>",
+                    "8500030     C$<1>",
+                    "8500040     D$<1>",
+                    "8500080     H$<1>",
+                    "8500090     I$<1>",
+                    "8500100     J$<1>",
+                ],
+            ),
+            (
+                "BITFELD",
+                &[
+                    "000001 000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000",
+                ],
+            ),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G B   8500010 8500040                                     %",
+                    "*A VE 8500010 8500040 000000                               %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00810  00811                 %",
+                    "8500030 C                     00820  00821                 %",
+                    "8500040 D                     00830                        %",
+                    "*Z 000002 000011                                           %",
+                    "*G B   8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000000                               %",
+                    "8500010 A                            00900                 %",
+                    "8500020 B                     00910  00911                 %",
+                    "8500010 A                     00920  00921                 %",
+                    "8500030 C                     00930                        %",
+                    "*Z 000003 000011                                           %",
+                    "*G B   8500080 8500100                                     %",
+                    "*A VE 8500080 8500100 000001                               %",
+                    "8500080 H                            02330                 %",
+                    "8500090 I                     02410  02412                 %",
+                    "8500100 J                     02440                        %",
+                ],
+            ),
+        ])
+    }
+
+    /// Describes each connection as "journey_id HH:MM HH:MM count_stops".
+    fn describe(connections: &[DirectConnection]) -> Vec<String> {
+        connections
+            .iter()
+            .map(|connection| {
+                format!(
+                    "{} {} {} {}",
+                    connection.journey().id(),
+                    connection.departure_time().format("%H:%M"),
+                    connection.arrival_time().format("%H:%M"),
+                    connection.count_stops(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn direct_connections_v207() {
+        let data_storage = direct_connection_fixture();
+        let from = date_time("2025-03-03", "07:00");
+
+        let connections =
+            data_storage.direct_connections(8500010, 8500040, from, TimeDelta::hours(3));
+        assert_eq!(vec!["1 08:00 08:30 2"], describe(&connections));
+        assert_eq!(TimeDelta::minutes(30), connections[0].duration());
+        assert_eq!(
+            vec![8500010, 8500020, 8500030, 8500040],
+            connections[0]
+                .route_section()
+                .iter()
+                .map(|route_entry| route_entry.stop_id())
+                .collect::<Vec<_>>()
+        );
+
+        // The window excludes the departures from its end on.
+        assert!(
+            data_storage
+                .direct_connections(8500010, 8500040, from, TimeDelta::hours(1))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn loop_route_v207() {
+        let data_storage = direct_connection_fixture();
+        let from = date_time("2025-03-03", "07:00");
+
+        // The journey 2 serves A twice, the last boarding before C is used.
+        assert_eq!(
+            vec!["1 08:00 08:20 1", "2 09:21 09:30 0"],
+            describe(&data_storage.direct_connections(8500010, 8500030, from, TimeDelta::hours(3)))
+        );
+        let connections =
+            data_storage.direct_connections(8500010, 8500020, from, TimeDelta::hours(3));
+        assert_eq!(
+            vec!["1 08:00 08:10 0", "2 09:00 09:10 0"],
+            describe(&connections)
+        );
+        assert_eq!(
+            (0, 1),
+            (
+                connections[1].departure_index(),
+                connections[1].arrival_index()
+            )
+        );
+        // And B to A, on the way back.
+        assert_eq!(
+            vec!["2 09:11 09:20 0"],
+            describe(&data_storage.direct_connections(8500020, 8500010, from, TimeDelta::hours(3)))
+        );
+    }
+
+    #[test]
+    fn arrival_before_departure_v207() {
+        let data_storage = direct_connection_fixture();
+        let from = date_time("2025-03-03", "07:00");
+
+        assert!(
+            data_storage
+                .direct_connections(8500040, 8500010, from, TimeDelta::hours(3))
+                .is_empty()
+        );
+        assert!(
+            data_storage
+                .direct_connections(8500030, 8500020, from, TimeDelta::hours(3))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn previous_service_day_after_midnight_v207() {
+        let data_storage = direct_connection_fixture();
+
+        // The journey 3 only operates on 03.03.2025, it serves I after midnight.
+        let from = date_time("2025-03-04", "00:00");
+        let connections =
+            data_storage.direct_connections(8500090, 8500100, from, TimeDelta::hours(1));
+        assert_eq!(vec!["3 00:12 00:40 0"], describe(&connections));
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+            connections[0].service_date()
+        );
+        assert_eq!(
+            date_time("2025-03-04", "00:40"),
+            connections[0].arrival_time()
+        );
+
+        let from = date_time("2025-03-05", "00:00");
+        assert!(
+            data_storage
+                .direct_connections(8500090, 8500100, from, TimeDelta::hours(1))
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,441 @@
+// ------------------------------------------------------------------------------------------------
+// --- Minimum exchange times
+// ------------------------------------------------------------------------------------------------
+
+use std::cmp::Reverse;
+
+use chrono::NaiveDate;
+
+use crate::{
+    models::{ExchangeTimeLine, Journey, Model},
+    storage::DataStorage,
+};
+
+/// The rule (and the HRDF file) from which a minimum exchange time comes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExchangeTimeRule {
+    /// Exchange time between two journeys (UMSTEIGZ file), with the id of the ExchangeTimeJourney.
+    Journey(i32),
+    /// Exchange time between two lines (UMSTEIGL file), with the id of the ExchangeTimeLine.
+    Line(i32),
+    /// Exchange time between two administrations (UMSTEIGV file), with the id of the ExchangeTimeAdministration.
+    Administration(i32),
+    /// Exchange time of the stop (UMSTEIGB file).
+    Stop,
+    /// Default exchange time (UMSTEIGB file, entry 9999999).
+    Default,
+}
+
+impl DataStorage {
+    /// Returns the minimum exchange time in minutes from the arriving journey to the departing journey at the stop,
+    /// and the rule which gave it. The stop is given by its index in the route of each journey, the date is the service
+    /// date of the arriving journey.
+    ///
+    /// The rules are applied in this order, the first one found is used:
+    /// 1. The journeys (UMSTEIGZ), if the entry operates on the date.
+    /// 2. The lines (UMSTEIGL), the entries of the stop before the ones for all stops.
+    ///    If several entries match, the one with the fewest wildcards is used.
+    /// 3. The administrations at the stop (UMSTEIGV).
+    /// 4. The stop (UMSTEIGB).
+    /// 5. The administrations at all stops (UMSTEIGV).
+    /// 6. The default exchange time (UMSTEIGB).
+    ///
+    /// For the rules 4 and 6, the InterCity exchange time is used if both journeys are InterCity trains.
+    pub fn exchange_time(
+        &self,
+        arriving_journey: &Journey,
+        arrival_index: usize,
+        departing_journey: &Journey,
+        departure_index: usize,
+        date: NaiveDate,
+    ) -> (i16, ExchangeTimeRule) {
+        let stop_id = arriving_journey.route()[arrival_index].stop_id();
+
+        if let Some(result) =
+            exchange_time_journey(self, stop_id, arriving_journey, departing_journey, date)
+        {
+            return result;
+        }
+
+        for line_stop_id in [Some(stop_id), None] {
+            if let Some(result) = exchange_time_line(
+                self,
+                line_stop_id,
+                (arriving_journey, arrival_index),
+                (departing_journey, departure_index),
+            ) {
+                return result;
+            }
+        }
+
+        if let Some(result) =
+            exchange_time_administration(self, Some(stop_id), arriving_journey, departing_journey)
+        {
+            return result;
+        }
+
+        let is_intercity = [arriving_journey, departing_journey]
+            .iter()
+            .all(|journey| journey.transport_type(self).designation() == "IC");
+        let choose = |(intercity, other): (i16, i16)| if is_intercity { intercity } else { other };
+
+        if let Some(exchange_time) = self
+            .stops()
+            .find(stop_id)
+            .and_then(|stop| stop.exchange_time())
+        {
+            return (choose(exchange_time), ExchangeTimeRule::Stop);
+        }
+
+        if let Some(result) =
+            exchange_time_administration(self, None, arriving_journey, departing_journey)
+        {
+            return result;
+        }
+
+        (
+            choose(self.default_exchange_time()),
+            ExchangeTimeRule::Default,
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+fn exchange_time_journey(
+    data_storage: &DataStorage,
+    stop_id: i32,
+    arriving_journey: &Journey,
+    departing_journey: &Journey,
+    date: NaiveDate,
+) -> Option<(i16, ExchangeTimeRule)> {
+    let key = (stop_id, arriving_journey.id(), departing_journey.id());
+    let mut ids: Vec<_> = data_storage
+        .exchange_times_journey_map()
+        .get(&key)?
+        .iter()
+        .copied()
+        .collect();
+    // Sorted to get the same result for the same data.
+    ids.sort_unstable();
+
+    ids.into_iter()
+        .filter_map(|id| data_storage.exchange_times_journey().find(id))
+        .find(|exchange_time| {
+            exchange_time.bit_field_id().is_none_or(|bit_field_id| {
+                data_storage
+                    .bit_fields_by_day()
+                    .contains(date, bit_field_id)
+            })
+        })
+        .map(|exchange_time| {
+            (
+                exchange_time.duration(),
+                ExchangeTimeRule::Journey(exchange_time.id()),
+            )
+        })
+}
+
+/// The journeys are given with the index of the stop in their route, as the direction can change along the route
+/// and a stop can be served several times (loops).
+fn exchange_time_line(
+    data_storage: &DataStorage,
+    line_stop_id: Option<i32>,
+    (arriving_journey, arrival_index): (&Journey, usize),
+    (departing_journey, departure_index): (&Journey, usize),
+) -> Option<(i16, ExchangeTimeRule)> {
+    let arriving_direction = arriving_journey.direction_type_at(arrival_index);
+    let departing_direction = departing_journey.direction_type_at(departure_index);
+
+    let matches = |exchange_time: &&ExchangeTimeLine| {
+        exchange_time
+            .line_1()
+            .matches(arriving_journey, arriving_direction, data_storage)
+            && exchange_time
+                .line_2()
+                .matches(departing_journey, departing_direction, data_storage)
+    };
+
+    data_storage
+        .exchange_times_line_by_stop_id()
+        .get(&line_stop_id)?
+        .iter()
+        .filter_map(|&id| data_storage.exchange_times_line().find(id))
+        .filter(matches)
+        .min_by_key(|exchange_time| {
+            let specificity =
+                exchange_time.line_1().specificity() + exchange_time.line_2().specificity();
+            (Reverse(specificity), exchange_time.id())
+        })
+        .map(|exchange_time| {
+            (
+                exchange_time.duration(),
+                ExchangeTimeRule::Line(exchange_time.id()),
+            )
+        })
+}
+
+fn exchange_time_administration(
+    data_storage: &DataStorage,
+    stop_id: Option<i32>,
+    arriving_journey: &Journey,
+    departing_journey: &Journey,
+) -> Option<(i16, ExchangeTimeRule)> {
+    let key = (
+        stop_id,
+        arriving_journey.administration().to_string(),
+        departing_journey.administration().to_string(),
+    );
+    let id = *data_storage.exchange_times_administration_map().get(&key)?;
+    let exchange_time = data_storage.exchange_times_administration().find(id)?;
+    Some((
+        exchange_time.duration(),
+        ExchangeTimeRule::Administration(id),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::data_storage_from;
+    use pretty_assertions::assert_eq;
+
+    /// At B (exchange times 4 and 3 minutes):
+    /// - IC 1 (line 1) arrives from A, IC 2 (line 2), B 3 (line 3, outward direction), B 4 (administration 000022),
+    ///   B 5 (administration 000033) and B 10 (no line) depart.
+    /// - B 11 (line 3) runs B, C, B, D and departs twice from B, first in the outward direction then in the return one.
+    ///
+    /// At D (default exchange times):
+    /// - IC 6 arrives from A, IC 7, B 8 (administration 000033) and B 9 depart.
+    fn exchange_time_fixture() -> DataStorage {
+        data_storage_from(&[
+            (
+                "BAHNHOF",
+                &[
+                    "8500010     A$<1>",
+                    "8500020     B$<1>",
+                    "8500030     C$<1>",
+                    "8500040     D$<1>",
+                ],
+            ),
+            (
+                "BITFELD",
+                &[
+                    "000001 000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000",
+                ],
+            ),
+            ("UMSTEIGB", &["9999999 05 02", "8500020 04 03"]),
+            (
+                "UMSTEIGV",
+                &["8500020 000011 000022 04", "@@@@@@@ 000011 000033 09"],
+            ),
+            (
+                "UMSTEIGZ",
+                &["8500020 000001 000011 000002 000011 010  000001 B"],
+            ),
+            (
+                "UMSTEIGL",
+                &[
+                    "8500020 000011 IC  1        * 000011 IC  2        * 007  B",
+                    "8500020 000011 IC  *        * 000011 B   *        * 006  B",
+                    "8500020 000011 IC  *        * 000011 B   3        H 005  B",
+                ],
+            ),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G IC  8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "*L 1        8500010 8500020                                %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00830                        %",
+                    "*Z 000002 000011                                           %",
+                    "*G IC  8500020 8500030                                     %",
+                    "*A VE 8500020 8500030 000000                               %",
+                    "*L 2        8500020 8500030                                %",
+                    "8500020 B                            00840                 %",
+                    "8500030 C                     00900                        %",
+                    "*Z 000003 000011                                           %",
+                    "*G B   8500020 8500030                                     %",
+                    "*A VE 8500020 8500030 000000                               %",
+                    "*L 3        8500020 8500030                                %",
+                    "*R H                                                       %",
+                    "8500020 B                            00845                 %",
+                    "8500030 C                     00910                        %",
+                    "*Z 000004 000022                                           %",
+                    "*G B   8500020 8500030                                     %",
+                    "*A VE 8500020 8500030 000000                               %",
+                    "8500020 B                            00850                 %",
+                    "8500030 C                     00920                        %",
+                    "*Z 000005 000033                                           %",
+                    "*G B   8500020 8500030                                     %",
+                    "*A VE 8500020 8500030 000000                               %",
+                    "8500020 B                            00855                 %",
+                    "8500030 C                     00930                        %",
+                    "*Z 000006 000011                                           %",
+                    "*G IC  8500010 8500040                                     %",
+                    "*A VE 8500010 8500040 000000                               %",
+                    "8500010 A                            00800                 %",
+                    "8500040 D                     00830                        %",
+                    "*Z 000007 000011                                           %",
+                    "*G IC  8500040 8500030                                     %",
+                    "*A VE 8500040 8500030 000000                               %",
+                    "8500040 D                            00840                 %",
+                    "8500030 C                     00900                        %",
+                    "*Z 000008 000033                                           %",
+                    "*G B   8500040 8500030                                     %",
+                    "*A VE 8500040 8500030 000000                               %",
+                    "8500040 D                            00845                 %",
+                    "8500030 C                     00910                        %",
+                    "*Z 000009 000011                                           %",
+                    "*G B   8500040 8500030                                     %",
+                    "*A VE 8500040 8500030 000000                               %",
+                    "8500040 D                            00850                 %",
+                    "8500030 C                     00920                        %",
+                    "*Z 000010 000011                                           %",
+                    "*G B   8500020 8500030                                     %",
+                    "*A VE 8500020 8500030 000000                               %",
+                    "8500020 B                            00900                 %",
+                    "8500030 C                     00940                        %",
+                    "*Z 000011 000011                                           %",
+                    "*G B   8500020 8500040                                     %",
+                    "*A VE 8500020 8500040 000000                               %",
+                    "*L 3        8500020 8500040                                %",
+                    "*R H         8500020 8500030                               %",
+                    "*R R         8500030 8500040                               %",
+                    "8500020 B                            00850                 %",
+                    "8500030 C                     00900  00900                 %",
+                    "8500020 B                     00910  00910                 %",
+                    "8500040 D                     00920                        %",
+                ],
+            ),
+        ])
+    }
+
+    fn exchange_time(
+        data_storage: &DataStorage,
+        stop_id: i32,
+        arriving_journey_id: i32,
+        departing_journey_id: i32,
+        day: u32,
+    ) -> (i16, ExchangeTimeRule) {
+        let journey_and_index = |journey_id| {
+            let journey = data_storage.journeys().find(journey_id).unwrap();
+            let index = journey
+                .route()
+                .iter()
+                .position(|route_entry| route_entry.stop_id() == stop_id)
+                .unwrap();
+            (journey, index)
+        };
+        let (arriving_journey, arrival_index) = journey_and_index(arriving_journey_id);
+        let (departing_journey, departure_index) = journey_and_index(departing_journey_id);
+
+        data_storage.exchange_time(
+            arriving_journey,
+            arrival_index,
+            departing_journey,
+            departure_index,
+            NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+        )
+    }
+
+    #[test]
+    fn journey_v207() {
+        let data_storage = exchange_time_fixture();
+
+        assert_eq!(
+            (10, ExchangeTimeRule::Journey(1)),
+            exchange_time(&data_storage, 8500020, 1, 2, 3)
+        );
+        // The entry only operates on 03.03.2025, the line entry is used on the other days.
+        assert_eq!(
+            (7, ExchangeTimeRule::Line(1)),
+            exchange_time(&data_storage, 8500020, 1, 2, 4)
+        );
+    }
+
+    #[test]
+    fn line_wildcards_v207() {
+        let data_storage = exchange_time_fixture();
+
+        // The entry with the line and the direction is more specific than the one with the wildcards.
+        assert_eq!(
+            (5, ExchangeTimeRule::Line(3)),
+            exchange_time(&data_storage, 8500020, 1, 3, 4)
+        );
+        // The wildcards also match the journeys without line or direction.
+        assert_eq!(
+            (6, ExchangeTimeRule::Line(2)),
+            exchange_time(&data_storage, 8500020, 1, 10, 4)
+        );
+    }
+
+    #[test]
+    fn line_direction_at_route_index_v207() {
+        let data_storage = exchange_time_fixture();
+        let arriving_journey = data_storage.journeys().find(1).unwrap();
+        let departing_journey = data_storage.journeys().find(11).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 3, 4).unwrap();
+
+        // The direction is the one of the departure from B, not the one of the first stop B in the route.
+        assert_eq!(
+            (5, ExchangeTimeRule::Line(3)),
+            data_storage.exchange_time(arriving_journey, 1, departing_journey, 0, date)
+        );
+        assert_eq!(
+            (6, ExchangeTimeRule::Line(2)),
+            data_storage.exchange_time(arriving_journey, 1, departing_journey, 2, date)
+        );
+    }
+
+    #[test]
+    fn administration_at_stop_v207() {
+        let data_storage = exchange_time_fixture();
+
+        // No line entry for the administration 000022.
+        assert_eq!(
+            (4, ExchangeTimeRule::Administration(1)),
+            exchange_time(&data_storage, 8500020, 1, 4, 4)
+        );
+    }
+
+    #[test]
+    fn stop_v207() {
+        let data_storage = exchange_time_fixture();
+
+        // The exchange time of the stop comes before the administrations at all stops.
+        assert_eq!(
+            (3, ExchangeTimeRule::Stop),
+            exchange_time(&data_storage, 8500020, 1, 5, 4)
+        );
+    }
+
+    #[test]
+    fn administration_at_all_stops_v207() {
+        let data_storage = exchange_time_fixture();
+
+        assert_eq!(
+            (9, ExchangeTimeRule::Administration(2)),
+            exchange_time(&data_storage, 8500040, 6, 8, 4)
+        );
+    }
+
+    #[test]
+    fn default_v207() {
+        let data_storage = exchange_time_fixture();
+
+        // The InterCity exchange time is used between two InterCity trains.
+        assert_eq!(
+            (5, ExchangeTimeRule::Default),
+            exchange_time(&data_storage, 8500040, 6, 7, 4)
+        );
+        assert_eq!(
+            (2, ExchangeTimeRule::Default),
+            exchange_time(&data_storage, 8500040, 6, 9, 4)
+        );
+    }
+}
@@ -0,0 +1,591 @@
+// ------------------------------------------------------------------------------------------------
+// --- Footpaths (stop connections)
+// ------------------------------------------------------------------------------------------------
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    models::{CoordinateSystem, Coordinates, Model, Stop, StopConnection},
+    storage::DataStorage,
+};
+
+/// Lower bound of the length of a degree of latitude, in meters.
+const METERS_PER_DEGREE_OF_LATITUDE: f64 = 110_000.0;
+
+/// How the stop connections (METABHF file) are followed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StopConnectionView {
+    /// Only from stop 1 to stop 2, as defined in the file.
+    Directed,
+    /// Also from stop 2 to stop 1, with the same duration, unless the reverse connection is defined.
+    /// The generated connections are left out between the stops having an official connection.
+    Undirected,
+}
+
+/// A walk from a stop to another through one or several stop connections.
+#[derive(Clone, Debug)]
+pub struct Footpath {
+    departure_stop_id: i32,
+    arrival_stop_id: i32,
+    duration: i16,                 // In minutes.
+    stop_connection_ids: Vec<i32>, // In walking order.
+}
+
+impl Footpath {
+    // Getters/Setters
+
+    pub fn departure_stop_id(&self) -> i32 {
+        self.departure_stop_id
+    }
+
+    pub fn arrival_stop_id(&self) -> i32 {
+        self.arrival_stop_id
+    }
+
+    pub fn duration(&self) -> i16 {
+        self.duration
+    }
+
+    pub fn stop_connection_ids(&self) -> &Vec<i32> {
+        &self.stop_connection_ids
+    }
+
+    // Functions
+
+    /// Returns the attributes (e.g. accessible, stairs) of the stop connections walked through, without duplicates.
+    pub fn attribute_ids(&self, data_storage: &DataStorage) -> Vec<i32> {
+        let mut attribute_ids = Vec::new();
+        for &id in &self.stop_connection_ids {
+            let attribute_id = data_storage
+                .stop_connections()
+                .find(id)
+                .and_then(|stop_connection| stop_connection.attribute_id());
+            if let Some(attribute_id) = attribute_id
+                && !attribute_ids.contains(&attribute_id)
+            {
+                attribute_ids.push(attribute_id);
+            }
+        }
+        attribute_ids
+    }
+}
+
+impl DataStorage {
+    /// Returns the stop connections leaving from the stop, sorted by arrival stop and duration.
+    pub fn footpaths(&self, stop_id: i32, view: StopConnectionView) -> Vec<Footpath> {
+        let index = match view {
+            StopConnectionView::Directed => self.stop_connections_by_stop_id(),
+            StopConnectionView::Undirected => self.undirected_stop_connections_by_stop_id(),
+        };
+        let stop_connections: Vec<_> = index
+            .get(&stop_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|&id| self.stop_connections().find(id))
+            .collect();
+        // The other stops of the official connections, in either direction.
+        // A generated connection is only an estimate, it never replaces an official one.
+        let official: FxHashSet<i32> = stop_connections
+            .iter()
+            .filter(|stop_connection| !stop_connection.is_generated())
+            .map(|stop_connection| other_stop_id(stop_connection, stop_id))
+            .collect();
+        let stop_connections: Vec<_> = stop_connections
+            .into_iter()
+            .filter(|stop_connection| {
+                !stop_connection.is_generated()
+                    || !official.contains(&other_stop_id(stop_connection, stop_id))
+            })
+            .collect();
+        // The arrival stops of the connections defined from the stop.
+        let defined: FxHashSet<i32> = stop_connections
+            .iter()
+            .filter(|stop_connection| stop_connection.stop_id_1() == stop_id)
+            .map(|stop_connection| stop_connection.stop_id_2())
+            .collect();
+
+        let mut footpaths: Vec<_> = stop_connections
+            .into_iter()
+            .filter_map(|stop_connection| {
+                let arrival_stop_id = other_stop_id(stop_connection, stop_id);
+                if stop_connection.stop_id_1() != stop_id && defined.contains(&arrival_stop_id) {
+                    // The reverse connection has its own duration.
+                    return None;
+                }
+                Some(Footpath {
+                    departure_stop_id: stop_id,
+                    arrival_stop_id,
+                    duration: stop_connection.duration(),
+                    stop_connection_ids: vec![stop_connection.id()],
+                })
+            })
+            .collect();
+        footpaths.sort_by_key(|footpath| {
+            (
+                footpath.arrival_stop_id,
+                footpath.duration,
+                footpath.stop_connection_ids[0],
+            )
+        });
+        footpaths
+    }
+
+    /// Returns the shortest walk from the stop to every stop reachable by chaining stop connections,
+    /// within max_duration minutes in total, sorted by duration and arrival stop.
+    pub fn transitive_footpaths(
+        &self,
+        stop_id: i32,
+        view: StopConnectionView,
+        max_duration: i16,
+    ) -> Vec<Footpath> {
+        // Dijkstra's algorithm, stop_id => shortest footpath found so far.
+        let mut shortest: FxHashMap<i32, Footpath> = FxHashMap::default();
+        let mut settled = FxHashSet::default();
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((0, stop_id)));
+
+        while let Some(Reverse((duration, current_stop_id))) = queue.pop() {
+            if !settled.insert(current_stop_id) {
+                continue;
+            }
+
+            for footpath in self.footpaths(current_stop_id, view) {
+                let next_duration = duration + footpath.duration;
+                let next_stop_id = footpath.arrival_stop_id;
+                if next_duration > max_duration
+                    || next_stop_id == stop_id
+                    || settled.contains(&next_stop_id)
+                    || shortest
+                        .get(&next_stop_id)
+                        .is_some_and(|known| known.duration <= next_duration)
+                {
+                    continue;
+                }
+
+                let mut stop_connection_ids = shortest
+                    .get(&current_stop_id)
+                    .map(|previous| previous.stop_connection_ids.clone())
+                    .unwrap_or_default();
+                stop_connection_ids.extend(footpath.stop_connection_ids);
+                shortest.insert(
+                    next_stop_id,
+                    Footpath {
+                        departure_stop_id: stop_id,
+                        arrival_stop_id: next_stop_id,
+                        duration: next_duration,
+                        stop_connection_ids,
+                    },
+                );
+                queue.push(Reverse((next_duration, next_stop_id)));
+            }
+        }
+
+        let mut footpaths: Vec<_> = shortest.into_values().collect();
+        footpaths.sort_by_key(|footpath| (footpath.duration, footpath.arrival_stop_id));
+        footpaths
+    }
+
+    /// Returns the data storage with additional stop connections between the stops which are at most max_distance
+    /// meters apart, in a straight line. The walking speed is in meters per minute.
+    /// The LV95 coordinates are used when both stops have them, the WGS84 coordinates otherwise.
+    /// The official connections (METABHF file) are kept as they are, the generated ones only complement them
+    /// and can be told apart with StopConnection::is_generated.
+    pub fn with_generated_footpaths(mut self, max_distance: f64, walking_speed: f64) -> Self {
+        let existing: FxHashSet<(i32, i32)> = self
+            .stop_connections()
+            .entries()
+            .into_iter()
+            .map(|stop_connection| (stop_connection.stop_id_1(), stop_connection.stop_id_2()))
+            .collect();
+        let mut next_id = self
+            .stop_connections()
+            .entries()
+            .into_iter()
+            .map(|stop_connection| stop_connection.id())
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        let mut stop_connections = Vec::new();
+        for (stop_id_1, stop_id_2, distance) in nearby_stops(self.stops().entries(), max_distance) {
+            let duration = ((distance / walking_speed).ceil() as i16).max(1);
+
+            for (from_stop_id, to_stop_id) in [(stop_id_1, stop_id_2), (stop_id_2, stop_id_1)] {
+                if existing.contains(&(from_stop_id, to_stop_id)) {
+                    continue;
+                }
+
+                let mut stop_connection =
+                    StopConnection::new(next_id, from_stop_id, to_stop_id, duration);
+                stop_connection.set_generated(true);
+                stop_connections.push(stop_connection);
+                next_id += 1;
+            }
+        }
+
+        log::info!("Generated {} footpaths.", stop_connections.len());
+        self.add_stop_connections(stop_connections);
+        self
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Returns the (stop_id_1, stop_id_2, distance) of the pairs of different stops at most max_distance meters apart,
+/// with stop_id_1 < stop_id_2, sorted.
+fn nearby_stops(stops: Vec<&Stop>, max_distance: f64) -> Vec<(i32, i32, f64)> {
+    let mut pairs = Vec::new();
+    for coordinate_system in [CoordinateSystem::LV95, CoordinateSystem::WGS84] {
+        // Sweeps the stops from south to north, only the stops in a band of height max_distance can be close enough.
+        let mut points: Vec<_> = stops
+            .iter()
+            .filter_map(|&stop| {
+                let coordinates = coordinates_in(stop, coordinate_system)?;
+                Some((northing_of(&coordinates)?, stop, coordinates))
+            })
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.id().cmp(&b.1.id())));
+
+        for (i, (northing_1, stop_1, coordinates_1)) in points.iter().enumerate() {
+            for (northing_2, stop_2, coordinates_2) in &points[i + 1..] {
+                if northing_2 - northing_1 > max_distance {
+                    break;
+                }
+                // The pairs having LV95 coordinates have already been handled.
+                if coordinate_system == CoordinateSystem::WGS84
+                    && stop_1.lv95_coordinates().is_some()
+                    && stop_2.lv95_coordinates().is_some()
+                {
+                    continue;
+                }
+
+                let Some(distance) = coordinates_1.distance(coordinates_2) else {
+                    continue;
+                };
+                if distance <= max_distance && stop_1.id() != stop_2.id() {
+                    let (stop_id_1, stop_id_2) = if stop_1.id() < stop_2.id() {
+                        (stop_1.id(), stop_2.id())
+                    } else {
+                        (stop_2.id(), stop_1.id())
+                    };
+                    pairs.push((stop_id_1, stop_id_2, distance));
+                }
+            }
+        }
+    }
+
+    pairs.sort_by_key(|&(stop_id_1, stop_id_2, _)| (stop_id_1, stop_id_2));
+    pairs
+}
+
+/// Returns the stop at the other end of the stop connection.
+fn other_stop_id(stop_connection: &StopConnection, stop_id: i32) -> i32 {
+    if stop_connection.stop_id_1() == stop_id {
+        stop_connection.stop_id_2()
+    } else {
+        stop_connection.stop_id_1()
+    }
+}
+
+fn coordinates_in(stop: &Stop, coordinate_system: CoordinateSystem) -> Option<Coordinates> {
+    match coordinate_system {
+        CoordinateSystem::LV95 => stop.lv95_coordinates(),
+        CoordinateSystem::WGS84 => stop.wgs84_coordinates(),
+    }
+}
+
+/// Distance to the north in meters, for the WGS84 coordinates it never exceeds the actual distance.
+fn northing_of(coordinates: &Coordinates) -> Option<f64> {
+    match coordinates.coordinate_system() {
+        CoordinateSystem::LV95 => coordinates.northing(),
+        CoordinateSystem::WGS84 => coordinates
+            .latitude()
+            .map(|latitude| latitude * METERS_PER_DEGREE_OF_LATITUDE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::data_storage_from;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    fn data_storage() -> DataStorage {
+        data_storage_from(&[
+            (
+                "BAHNHOF",
+                &[
+                    "8500010     A$<1>",
+                    "8500020     B$<1>",
+                    "8500030     C$<1>",
+                    "8500040     D$<1>",
+                ],
+            ),
+            (
+                "BFKOORD_LV95",
+                &[
+                    "8500010 2600000.000 1200000.000       0",
+                    "8500020 2600300.000 1200000.000       0",
+                    "8500030 2600600.000 1200000.000       0",
+                    "8500040 2610000.000 1200000.000       0",
+                ],
+            ),
+            ("ATTRIBUT", &["RA 0   5  5"]),
+            (
+                "METABHF",
+                &[
+                    "8500010 8500020 003",
+                    "*A RA",
+                    "8500020 8500030 004",
+                    "8500030 8500020 006",
+                ],
+            ),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G B   8500040 8500010                                     %",
+                    "*A VE 8500040 8500010 000000                               %",
+                    "8500040 D                            00800                 %",
+                    "8500010 A                     00810                        %",
+                ],
+            ),
+        ])
+    }
+
+    /// Returns the (stop_id_2, duration, is_generated) of the stop connections leaving from the stop.
+    fn stop_connections_from(data_storage: &DataStorage, stop_id: i32) -> Vec<(i32, i16, bool)> {
+        let mut stop_connections: Vec<_> = data_storage
+            .stop_connections()
+            .entries()
+            .into_iter()
+            .filter(|stop_connection| stop_connection.stop_id_1() == stop_id)
+            .map(|stop_connection| {
+                (
+                    stop_connection.stop_id_2(),
+                    stop_connection.duration(),
+                    stop_connection.is_generated(),
+                )
+            })
+            .collect();
+        stop_connections.sort_unstable();
+        stop_connections
+    }
+
+    fn summary(footpaths: &[Footpath]) -> Vec<(i32, i16, usize)> {
+        footpaths
+            .iter()
+            .map(|footpath| {
+                (
+                    footpath.arrival_stop_id(),
+                    footpath.duration(),
+                    footpath.stop_connection_ids().len(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn generated_footpaths_lv95_v207() {
+        // A to C is 600 m: 8 minutes at 75 m/min. D is too far away.
+        let data_storage = data_storage().with_generated_footpaths(600.0, 75.0);
+
+        // The official connections keep their durations.
+        assert_eq!(
+            vec![(8500020, 3, false), (8500030, 8, true)],
+            stop_connections_from(&data_storage, 8500010)
+        );
+        assert_eq!(
+            vec![(8500010, 4, true), (8500030, 4, false)],
+            stop_connections_from(&data_storage, 8500020)
+        );
+        assert_eq!(
+            vec![(8500010, 8, true), (8500020, 6, false)],
+            stop_connections_from(&data_storage, 8500030)
+        );
+        assert!(stop_connections_from(&data_storage, 8500040).is_empty());
+        assert_eq!(
+            Some(&FxHashSet::from_iter([2, 4])),
+            data_storage.stop_connections_by_stop_id().get(&8500020)
+        );
+    }
+
+    #[test]
+    fn generated_footpaths_max_distance_v207() {
+        // Just below the 600 m between A and C.
+        let data_storage = data_storage().with_generated_footpaths(599.0, 70.0);
+
+        // 300 m at 70 m/min: the duration is rounded up to 5 minutes.
+        assert_eq!(
+            vec![(8500020, 3, false)],
+            stop_connections_from(&data_storage, 8500010)
+        );
+        assert_eq!(
+            vec![(8500010, 5, true), (8500030, 4, false)],
+            stop_connections_from(&data_storage, 8500020)
+        );
+    }
+
+    #[test]
+    fn generated_footpaths_wgs84_v207() {
+        // E and F are 0.0027° of latitude apart, about 300.2 m. G only has LV95 coordinates.
+        let wgs84_data_storage = || {
+            data_storage_from(&[
+                (
+                    "BAHNHOF",
+                    &[
+                        "8500050     E$<1>",
+                        "8500060     F$<1>",
+                        "8500070     G$<1>",
+                    ],
+                ),
+                ("BFKOORD_LV95", &["8500070 2600000.000 1200000.000       0"]),
+                (
+                    "BFKOORD_WGS",
+                    &[
+                        "8500050    7.000000   47.000000       0",
+                        "8500060    7.000000   47.002700       0",
+                    ],
+                ),
+            ])
+        };
+
+        let coordinates = |data_storage: &DataStorage, stop_id| {
+            data_storage
+                .stops()
+                .find(stop_id)
+                .unwrap()
+                .wgs84_coordinates()
+                .unwrap()
+        };
+        let data_storage = wgs84_data_storage();
+        let distance = coordinates(&data_storage, 8500050)
+            .distance(&coordinates(&data_storage, 8500060))
+            .unwrap();
+        assert!((distance - 300.2).abs() < 0.1);
+
+        assert!(
+            data_storage
+                .with_generated_footpaths(300.0, 75.0)
+                .stop_connections()
+                .entries()
+                .is_empty()
+        );
+
+        let data_storage = wgs84_data_storage().with_generated_footpaths(301.0, 75.0);
+        assert_eq!(
+            vec![(8500060, 5, true)],
+            stop_connections_from(&data_storage, 8500050)
+        );
+        assert_eq!(2, data_storage.stop_connections().entries().len());
+    }
+
+    #[test]
+    fn directed_and_undirected_views_v207() {
+        let data_storage = data_storage();
+
+        let directed = data_storage.footpaths(8500020, StopConnectionView::Directed);
+        assert_eq!(vec![(8500030, 4, 1)], summary(&directed));
+
+        // A to B is mirrored, C to B is left out as B to C is defined.
+        let undirected = data_storage.footpaths(8500020, StopConnectionView::Undirected);
+        assert_eq!(vec![(8500010, 3, 1), (8500030, 4, 1)], summary(&undirected));
+        let undirected = data_storage.footpaths(8500030, StopConnectionView::Undirected);
+        assert_eq!(vec![(8500020, 6, 1)], summary(&undirected));
+    }
+
+    #[test]
+    fn undirected_view_keeps_official_durations_v207() {
+        // 300 m at 75 m/min: the generated connections take 4 minutes.
+        let data_storage = data_storage().with_generated_footpaths(400.0, 75.0);
+
+        // B to A only exists as a generated connection.
+        let directed = data_storage.footpaths(8500020, StopConnectionView::Directed);
+        assert_eq!(vec![(8500010, 4, 1), (8500030, 4, 1)], summary(&directed));
+        let stop_connection = data_storage
+            .stop_connections()
+            .find(directed[0].stop_connection_ids()[0])
+            .unwrap();
+        assert!(stop_connection.is_generated());
+
+        // In the undirected view, the official A to B connection is mirrored instead.
+        let undirected = data_storage.footpaths(8500020, StopConnectionView::Undirected);
+        assert_eq!(vec![(8500010, 3, 1), (8500030, 4, 1)], summary(&undirected));
+        let stop_connection = data_storage
+            .stop_connections()
+            .find(undirected[0].stop_connection_ids()[0])
+            .unwrap();
+        assert!(!stop_connection.is_generated());
+        assert_eq!(8500010, stop_connection.stop_id_1());
+
+        let undirected = data_storage.footpaths(8500030, StopConnectionView::Undirected);
+        assert_eq!(vec![(8500020, 6, 1)], summary(&undirected));
+    }
+
+    #[test]
+    fn transitive_footpaths_v207() {
+        let data_storage = data_storage();
+
+        let footpaths =
+            data_storage.transitive_footpaths(8500010, StopConnectionView::Directed, 10);
+        assert_eq!(vec![(8500020, 3, 1), (8500030, 7, 2)], summary(&footpaths));
+        let stop_connection_ids: Vec<_> = footpaths[1]
+            .stop_connection_ids()
+            .iter()
+            .map(|&id| {
+                let stop_connection = data_storage.stop_connections().find(id).unwrap();
+                (stop_connection.stop_id_1(), stop_connection.stop_id_2())
+            })
+            .collect();
+        assert_eq!(
+            vec![(8500010, 8500020), (8500020, 8500030)],
+            stop_connection_ids
+        );
+
+        let footpaths = data_storage.transitive_footpaths(8500010, StopConnectionView::Directed, 6);
+        assert_eq!(vec![(8500020, 3, 1)], summary(&footpaths));
+
+        // Back from C to A: C to B has its own duration (6 minutes), B to A is mirrored.
+        let footpaths =
+            data_storage.transitive_footpaths(8500030, StopConnectionView::Undirected, 10);
+        assert_eq!(vec![(8500020, 6, 1), (8500010, 9, 2)], summary(&footpaths));
+    }
+
+    #[test]
+    fn walk_leg_attributes_v207() {
+        let data_storage = data_storage();
+        let departure_at = NaiveDate::from_ymd_opt(2025, 3, 3)
+            .unwrap()
+            .and_hms_opt(7, 55, 0)
+            .unwrap();
+
+        let itineraries = data_storage.plan_journeys(8500040, 8500020, departure_at, 1);
+        assert_eq!(1, itineraries.len());
+        let legs = itineraries[0].legs();
+        assert_eq!(2, legs.len());
+        let crate::routing::Leg::Walk(walk_leg) = &legs[1] else {
+            panic!("The last leg must be a walk.");
+        };
+        assert_eq!(8500010, walk_leg.departure_stop_id());
+        assert_eq!(
+            3,
+            (walk_leg.arrival_time() - walk_leg.departure_time()).num_minutes()
+        );
+        // The attribute RA of the A to B connection.
+        let stop_connection_id = data_storage.footpaths(8500010, StopConnectionView::Directed)[0]
+            .stop_connection_ids()[0];
+        let attribute_id = data_storage
+            .stop_connections()
+            .find(stop_connection_id)
+            .unwrap()
+            .attribute_id()
+            .unwrap();
+        assert_eq!(&vec![attribute_id], walk_leg.attribute_ids());
+        assert_eq!(1, walk_leg.attributes(&data_storage).len());
+    }
+}
@@ -114,6 +114,10 @@ impl Hrdf {
         &self.data_storage
     }
 
+    pub fn into_data_storage(self) -> DataStorage {
+        self.data_storage
+    }
+
     // Functions
 
     pub fn build_cache(&self, path: &str) -> Result<(), Box<dyn Error>> {
@@ -1,14 +1,32 @@
+mod board;
+mod direct_connection;
+mod exchange_time;
+mod footpath;
 mod hrdf;
 mod models;
+mod operating_days;
 mod parsing;
+mod routing;
 mod storage;
+mod timetable_set;
 mod utils;
+mod vehicle_run;
 
+pub use board::BoardEntry;
+pub use direct_connection::DirectConnection;
+pub use exchange_time::ExchangeTimeRule;
+pub use footpath::{Footpath, StopConnectionView};
 pub use hrdf::Hrdf;
 pub use models::*;
+pub use operating_days::describe_operating_days;
+pub use routing::{
+    ConnectionTable, Itinerary, Leg, Reachability, RideLeg, TravelTimeMatrix, WalkLeg,
+};
 pub use storage::DataStorage;
+pub use timetable_set::{TimetableChange, TimetableSet};
 pub use utils::timetable_end_date;
 pub use utils::timetable_start_date;
+pub use vehicle_run::{ThroughJourney, VehicleRun};
 
 #[cfg(test)]
 mod tests {
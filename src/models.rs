@@ -1,20 +1,27 @@
 use std::{
     collections::BTreeSet,
     hash::{DefaultHasher, Hash, Hasher},
+    str::FromStr,
 };
 
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{
+    DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike, Weekday,
+};
+use chrono_tz::Tz;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use strum_macros::{self, Display, EnumString};
 
 use crate::{
     storage::DataStorage,
-    utils::{add_1_day, sub_1_day},
+    utils::{add_1_day, count_days_between_two_dates, sub_1_day, to_timezone},
 };
 
 pub(crate) type JourneyId = (i32, String); // (legacy_id, administration)
 
+/// The timezone of the Swiss HRDF times. Other timezones can be passed to the *_zoned functions for non-Swiss data.
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Europe::Zurich;
+
 // ------------------------------------------------------------------------------------------------
 // --- Model
 // ------------------------------------------------------------------------------------------------
@@ -91,23 +98,228 @@ impl Attribute {
 // --- BitField
 // ------------------------------------------------------------------------------------------------
 
+pub const BIT_FIELD_WORDS: usize = 6; // A BITFELD entry has 96 hexadecimal digits, i.e. 384 bits.
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BitField {
     id: i32,
-    bits: Vec<u8>,
+    words: [u64; BIT_FIELD_WORDS], // The bit i of the BITFELD entry is stored in words[i / 64] at position i % 64.
 }
 
 impl_Model!(BitField);
 
 impl BitField {
-    pub fn new(id: i32, bits: Vec<u8>) -> Self {
-        Self { id, bits }
+    pub fn new(id: i32, words: [u64; BIT_FIELD_WORDS]) -> Self {
+        Self { id, words }
+    }
+
+    // Getters/Setters
+
+    pub fn words(&self) -> &[u64; BIT_FIELD_WORDS] {
+        &self.words
+    }
+
+    // Functions
+
+    /// Returns true if the bit field is active on the day. The day 0 is the start date of the timetable period.
+    pub fn contains(&self, day: usize) -> bool {
+        // The first two bits must be ignored.
+        let i = day + 2;
+        self.words
+            .get(i / 64)
+            .is_some_and(|word| (word >> (i % 64)) & 1 == 1)
+    }
+
+    /// Returns true if the bit field is active on the date.
+    /// The timetable period is given by the start and end dates of the ECKDATEN file, the start date being the day 0.
+    pub fn is_active_on(
+        &self,
+        date: NaiveDate,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> bool {
+        if date < start_date || date > end_date {
+            return false;
+        }
+
+        // unwrap: The date is after the start date, so the number of days is positive.
+        let day = usize::try_from((date - start_date).num_days()).unwrap();
+        self.contains(day)
+    }
+
+    /// Returns the dates of the timetable period on which the bit field is active, in chronological order.
+    pub fn operating_dates(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> impl Iterator<Item = NaiveDate> + '_ {
+        start_date
+            .iter_days()
+            .take(count_days_between_two_dates(start_date, end_date))
+            .enumerate()
+            .filter(|&(day, _)| self.contains(day))
+            .map(|(_, date)| date)
+    }
+
+    pub fn count_operating_days(&self, start_date: NaiveDate, end_date: NaiveDate) -> usize {
+        self.operating_dates(start_date, end_date).count()
+    }
+
+    /// Returns a new bit field whose day 0 is the day start_day of this bit field, limited to num_days days.
+    pub fn rebased(&self, start_day: usize, num_days: usize) -> BitField {
+        let mut words = [0u64; BIT_FIELD_WORDS];
+        for day in (0..num_days).filter(|&day| self.contains(start_day + day)) {
+            // The first two bits must be ignored.
+            let i = day + 2;
+            if let Some(word) = words.get_mut(i / 64) {
+                *word |= 1 << (i % 64);
+            }
+        }
+        BitField::new(self.id, words)
+    }
+
+    /// Returns true if the bit field is not active on any day.
+    pub fn is_empty(&self) -> bool {
+        // The first two bits must be ignored.
+        self.words[0] & !0b11 == 0 && self.words[1..].iter().all(|&word| word == 0)
+    }
+
+    /// Returns a new bit field, active on the days on which at least one of the bit fields is active.
+    pub fn union(&self, other: &BitField, id: i32) -> BitField {
+        self.combine(other, id, |a, b| a | b)
+    }
+
+    /// Returns a new bit field, active on the days on which both bit fields are active.
+    pub fn intersection(&self, other: &BitField, id: i32) -> BitField {
+        self.combine(other, id, |a, b| a & b)
+    }
+
+    /// Returns a new bit field, active on the days on which self is active but not other.
+    pub fn difference(&self, other: &BitField, id: i32) -> BitField {
+        self.combine(other, id, |a, b| a & !b)
+    }
+
+    fn combine(&self, other: &BitField, id: i32, operation: impl Fn(u64, u64) -> u64) -> BitField {
+        let words = std::array::from_fn(|i| operation(self.words[i], other.words[i]));
+        BitField::new(id, words)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- BitFieldsByDay
+// ------------------------------------------------------------------------------------------------
+
+/// Index of the bit fields active on each day of the timetable period.
+/// For each day, the active bit fields are stored in a bit set over the positions of the bit fields in bit_field_ids.
+/// The bit field 0 means "every day" and is therefore active on every day of the timetable period.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BitFieldsByDay {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    bit_field_ids: Vec<i32>,
+    positions: FxHashMap<i32, usize>, // bit_field_id => position in bit_field_ids
+    days: Vec<Vec<u64>>,
+}
+
+impl BitFieldsByDay {
+    pub fn new(bit_fields: Vec<&BitField>, start_date: NaiveDate, end_date: NaiveDate) -> Self {
+        let mut bit_field_ids: Vec<i32> =
+            bit_fields.iter().map(|bit_field| bit_field.id()).collect();
+        bit_field_ids.sort_unstable();
+        let positions = bit_field_ids
+            .iter()
+            .enumerate()
+            .map(|(position, &id)| (id, position))
+            .collect::<FxHashMap<_, _>>();
+
+        let num_days = count_days_between_two_dates(start_date, end_date);
+        let mut days = vec![vec![0u64; bit_field_ids.len().div_ceil(64)]; num_days];
+        for bit_field in bit_fields {
+            let position = positions[&bit_field.id()];
+            for (day, words) in days.iter_mut().enumerate() {
+                if bit_field.contains(day) {
+                    words[position / 64] |= 1 << (position % 64);
+                }
+            }
+        }
+
+        Self {
+            start_date,
+            end_date,
+            bit_field_ids,
+            positions,
+            days,
+        }
+    }
+
+    // Functions
+
+    /// Returns true if the bit field is active on the date.
+    pub fn contains(&self, date: NaiveDate, bit_field_id: i32) -> bool {
+        let Some(words) = self.words_of(date) else {
+            return false;
+        };
+
+        if bit_field_id == 0 {
+            return true;
+        }
+
+        self.positions
+            .get(&bit_field_id)
+            .is_some_and(|&position| (words[position / 64] >> (position % 64)) & 1 == 1)
+    }
+
+    /// Returns the ids of the bit fields active on the date, including the bit field 0. The ids are sorted.
+    /// If the date is outside the timetable period, no id is returned.
+    pub fn bit_field_ids_on(&self, date: NaiveDate) -> Vec<i32> {
+        let Some(words) = self.words_of(date) else {
+            return Vec::new();
+        };
+
+        let active = self
+            .bit_field_ids
+            .iter()
+            .enumerate()
+            .filter(|&(position, _)| (words[position / 64] >> (position % 64)) & 1 == 1)
+            .map(|(_, &id)| id);
+        std::iter::once(0).chain(active).collect()
+    }
+
+    fn words_of(&self, date: NaiveDate) -> Option<&Vec<u64>> {
+        if date < self.start_date || date > self.end_date {
+            return None;
+        }
+
+        let day = usize::try_from((date - self.start_date).num_days()).ok()?;
+        self.days.get(day)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- BorderPoint
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BorderPoint {
+    id: i32, // The stop number of the border point.
+    designation: Option<String>,
+}
+
+impl_Model!(BorderPoint);
+
+impl BorderPoint {
+    pub fn new(id: i32, designation: Option<String>) -> Self {
+        Self { id, designation }
     }
 
     // Getters/Setters
 
-    pub fn bits(&self) -> &Vec<u8> {
-        &self.bits
+    pub fn stop_id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn designation(&self) -> Option<&str> {
+        self.designation.as_deref()
     }
 }
 
@@ -204,6 +416,56 @@ impl Coordinates {
             CoordinateSystem::LV95 => None,
         }
     }
+
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.coordinate_system
+    }
+
+    // Functions
+
+    /// Returns the distance in meters to the other coordinates, None if they are not in the same coordinate system.
+    /// The LV95 distance is euclidean, the WGS84 distance is the great-circle distance.
+    pub fn distance(&self, other: &Coordinates) -> Option<f64> {
+        const EARTH_RADIUS: f64 = 6_371_000.0;
+
+        match (self.coordinate_system, other.coordinate_system) {
+            (CoordinateSystem::LV95, CoordinateSystem::LV95) => {
+                Some((self.x - other.x).hypot(self.y - other.y))
+            }
+            (CoordinateSystem::WGS84, CoordinateSystem::WGS84) => {
+                let (latitude_1, latitude_2) = (self.x.to_radians(), other.x.to_radians());
+                let delta_latitude = latitude_2 - latitude_1;
+                let delta_longitude = (other.y - self.y).to_radians();
+                let a = (delta_latitude / 2.0).sin().powi(2)
+                    + latitude_1.cos() * latitude_2.cos() * (delta_longitude / 2.0).sin().powi(2);
+                Some(2.0 * EARTH_RADIUS * a.sqrt().asin())
+            }
+            _ => None,
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- DayType
+// ------------------------------------------------------------------------------------------------
+
+/// Classification of the dates used to compare the service levels. The public holidays count as Sundays.
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum DayType {
+    Weekday,
+    Saturday,
+    SundayOrHoliday,
+}
+
+impl DayType {
+    pub fn new(date: NaiveDate, is_holiday: bool) -> Self {
+        match date.weekday() {
+            _ if is_holiday => DayType::SundayOrHoliday,
+            Weekday::Sat => DayType::Saturday,
+            Weekday::Sun => DayType::SundayOrHoliday,
+            _ => DayType::Weekday,
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -222,6 +484,12 @@ impl Direction {
     pub fn new(id: i32, name: String) -> Self {
         Self { id, name }
     }
+
+    // Getters/Setters
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -257,6 +525,16 @@ impl Holiday {
     pub fn new(id: i32, date: NaiveDate, name: FxHashMap<Language, String>) -> Self {
         Self { id, date, name }
     }
+
+    // Getters/Setters
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn name(&self, language: Language) -> Option<&str> {
+        self.name.get(&language).map(|s| s.as_str())
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -318,6 +596,8 @@ impl ExchangeTimeAdministration {
 pub struct ExchangeTimeJourney {
     id: i32,
     stop_id: i32,
+    journey_id_1: i32,
+    journey_id_2: i32,
     journey_legacy_id_1: i32,
     administration_1: String,
     journey_legacy_id_2: i32,
@@ -330,10 +610,13 @@ pub struct ExchangeTimeJourney {
 impl_Model!(ExchangeTimeJourney);
 
 impl ExchangeTimeJourney {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: i32,
         stop_id: i32,
+        journey_id_1: i32,
         (journey_legacy_id_1, administration_1): JourneyId,
+        journey_id_2: i32,
         (journey_legacy_id_2, administration_2): JourneyId,
         duration: i16,
         is_guaranteed: bool,
@@ -342,6 +625,8 @@ impl ExchangeTimeJourney {
         Self {
             id,
             stop_id,
+            journey_id_1,
+            journey_id_2,
             journey_legacy_id_1,
             administration_1,
             journey_legacy_id_2,
@@ -358,6 +643,14 @@ impl ExchangeTimeJourney {
         self.stop_id
     }
 
+    pub fn journey_id_1(&self) -> i32 {
+        self.journey_id_1
+    }
+
+    pub fn journey_id_2(&self) -> i32 {
+        self.journey_id_2
+    }
+
     pub fn journey_legacy_id_1(&self) -> i32 {
         self.journey_legacy_id_1
     }
@@ -381,6 +674,10 @@ impl ExchangeTimeJourney {
     pub fn bit_field_id(&self) -> Option<i32> {
         self.bit_field_id
     }
+
+    pub fn is_guaranteed(&self) -> bool {
+        self.is_guaranteed
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -399,8 +696,9 @@ pub struct ExchangeTimeLine {
 
 impl_Model!(ExchangeTimeLine);
 
+/// A None line or direction is a wildcard (* in the UMSTEIGL file).
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct LineInfo {
+pub struct LineInfo {
     administration: String,
     transport_type_id: i32,
     line_id: Option<String>,
@@ -408,7 +706,7 @@ pub(crate) struct LineInfo {
 }
 
 impl LineInfo {
-    pub(crate) fn new(
+    pub fn new(
         administration: String,
         transport_type_id: i32,
         line_id: Option<String>,
@@ -421,10 +719,51 @@ impl LineInfo {
             direction,
         }
     }
+
+    // Getters/Setters
+
+    pub fn administration(&self) -> &str {
+        &self.administration
+    }
+
+    pub fn transport_type_id(&self) -> i32 {
+        self.transport_type_id
+    }
+
+    pub fn line_id(&self) -> Option<&str> {
+        self.line_id.as_deref()
+    }
+
+    pub fn direction(&self) -> Option<DirectionType> {
+        self.direction
+    }
+
+    // Functions
+
+    /// Returns true if the journey, running in the direction, belongs to the line. The wildcards match everything.
+    pub fn matches(
+        &self,
+        journey: &Journey,
+        direction: Option<DirectionType>,
+        data_storage: &DataStorage,
+    ) -> bool {
+        self.administration == journey.administration()
+            && self.transport_type_id == journey.transport_type_id()
+            && self
+                .line_id
+                .as_deref()
+                .is_none_or(|line_id| journey.line_designation(data_storage) == Some(line_id))
+            && self.direction.is_none_or(|value| direction == Some(value))
+    }
+
+    /// Number of fields which are not wildcards. Used to choose the most specific entry.
+    pub fn specificity(&self) -> usize {
+        usize::from(self.line_id.is_some()) + usize::from(self.direction.is_some())
+    }
 }
 
 impl ExchangeTimeLine {
-    pub(crate) fn new(
+    pub fn new(
         id: i32,
         stop_id: Option<i32>,
         line_1: LineInfo,
@@ -441,6 +780,28 @@ impl ExchangeTimeLine {
             is_guaranteed,
         }
     }
+
+    // Getters/Setters
+
+    pub fn stop_id(&self) -> Option<i32> {
+        self.stop_id
+    }
+
+    pub fn line_1(&self) -> &LineInfo {
+        &self.line_1
+    }
+
+    pub fn line_2(&self) -> &LineInfo {
+        &self.line_2
+    }
+
+    pub fn duration(&self) -> i16 {
+        self.duration
+    }
+
+    pub fn is_guaranteed(&self) -> bool {
+        self.is_guaranteed
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -465,20 +826,59 @@ impl InformationText {
 
     // Getters/Setters
 
+    pub fn content(&self, language: Language) -> Option<&str> {
+        self.content.get(&language).map(|s| s.as_str())
+    }
+
     pub fn set_content(&mut self, language: Language, value: &str) {
         self.content.insert(language, value.to_string());
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+// --- InformationTextCode
+// ------------------------------------------------------------------------------------------------
+
+/// Code of an *I line (INFOTEXTCODE) in FPLAN.
+/// Codes which are not in the list of permitted Swiss codes are kept as is in `Other`.
+#[derive(
+    Clone, Debug, Default, Display, Eq, Hash, PartialEq, EnumString, Serialize, Deserialize,
+)]
+pub enum InformationTextCode {
+    /// Simple note displayed to the passengers.
+    #[default]
+    #[strum(serialize = "hi")]
+    Note,
+
+    /// Swiss Journey ID (SJYID).
+    #[strum(serialize = "JY")]
+    SwissJourneyId,
+
+    /// Region information, only used by the transport company 801.
+    #[strum(serialize = "RN")]
+    Region,
+
+    /// Name of the train (e.g. Glacier Express).
+    #[strum(serialize = "ZN")]
+    TrainName,
+
+    #[strum(default)]
+    Other(String),
+}
+
 // ------------------------------------------------------------------------------------------------
 // --- Journey
 // ------------------------------------------------------------------------------------------------
 
+/// Timezone-aware (arrival, departure) date-times of a route entry.
+pub type ZonedRouteEntry = (Option<DateTime<Tz>>, Option<DateTime<Tz>>);
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Journey {
     id: i32,
     legacy_id: i32,
     administration: String,
+    region: Option<String>, // Only set for the TU code 801, where the journey number is only unique within a region.
     metadata: FxHashMap<JourneyMetadataType, Vec<JourneyMetadataEntry>>,
     route: Vec<JourneyRouteEntry>,
 }
@@ -491,6 +891,7 @@ impl Journey {
             id,
             legacy_id,
             administration,
+            region: None,
             metadata: FxHashMap::default(),
             route: Vec::new(),
         }
@@ -506,7 +907,15 @@ impl Journey {
         self.legacy_id
     }
 
-    fn metadata(&self) -> &FxHashMap<JourneyMetadataType, Vec<JourneyMetadataEntry>> {
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    pub fn set_region(&mut self, value: String) {
+        self.region = Some(value);
+    }
+
+    pub(crate) fn metadata(&self) -> &FxHashMap<JourneyMetadataType, Vec<JourneyMetadataEntry>> {
         &self.metadata
     }
 
@@ -524,6 +933,17 @@ impl Journey {
         self.route.push(entry);
     }
 
+    /// Keeps only the metadata entries for which the predicate returns true.
+    pub fn retain_metadata_entries(
+        &mut self,
+        mut f: impl FnMut(JourneyMetadataType, &JourneyMetadataEntry) -> bool,
+    ) {
+        for (&k, entries) in self.metadata.iter_mut() {
+            entries.retain(|entry| f(k, entry));
+        }
+        self.metadata.retain(|_, entries| !entries.is_empty());
+    }
+
     pub fn bit_field_id(&self) -> Option<i32> {
         // unwrap: There will always be a BitField entry.
         let entry = &self.metadata().get(&JourneyMetadataType::BitField).unwrap()[0];
@@ -540,6 +960,53 @@ impl Journey {
         entry.resource_id.unwrap()
     }
 
+    /// Returns true if the journey operates on the date. The date must correspond to the route's first entry.
+    pub fn operates_on(&self, date: NaiveDate, data_storage: &DataStorage) -> bool {
+        let period = data_storage.timetable_period();
+
+        match self.bit_field_id() {
+            // No bit field or the bit field 0 means that the journey operates every day.
+            None | Some(0) => period.contains(date),
+            Some(bit_field_id) => {
+                data_storage
+                    .bit_fields()
+                    .find(bit_field_id)
+                    .is_some_and(|bit_field| {
+                        bit_field.is_active_on(date, period.start_date(), period.end_date())
+                    })
+            }
+        }
+    }
+
+    /// Border crossings of the journey (*GR lines). For each entry, the resource is the border point,
+    /// the from/until stops are the last stop before and the first stop after the border.
+    pub fn border_crossings(&self) -> &[JourneyMetadataEntry] {
+        self.metadata()
+            .get(&JourneyMetadataType::BorderPoint)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Stop-specific validities of the journey (*SH lines). For each entry, the from stop is only served
+    /// on the days of the bit field.
+    pub fn stop_bit_fields(&self) -> &[JourneyMetadataEntry] {
+        self.metadata()
+            .get(&JourneyMetadataType::StopBitField)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns false if the stop is only served on some days (*SH lines) and the date is not one of them.
+    pub fn serves_stop_on(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+        data_storage: &DataStorage,
+    ) -> bool {
+        self.stop_bit_fields()
+            .iter()
+            .filter(|entry| entry.from_stop_id() == Some(stop_id))
+            .all(|entry| entry.is_valid_on(date, data_storage))
+    }
+
     pub fn transport_type<'a>(&'a self, data_storage: &'a DataStorage) -> &'a TransportType {
         data_storage
             .transport_types()
@@ -547,6 +1014,114 @@ impl Journey {
             .unwrap_or_else(|| panic!("Transport type {:?} not found.", self.transport_type_id()))
     }
 
+    /// Returns the public designation of the journey's line (e.g. "1" for the IC 1), if any.
+    /// For a reference to the LINIE file, the short name of the line is used, or its name if it has no short name.
+    pub fn line_designation<'a>(&'a self, data_storage: &'a DataStorage) -> Option<&'a str> {
+        let entry = self.metadata().get(&JourneyMetadataType::Line)?.first()?;
+
+        match (entry.resource_id, entry.extra_field_1.as_deref()) {
+            (Some(line_id), _) => {
+                let line = data_storage.lines().find(line_id)?;
+                if line.short_name().is_empty() {
+                    Some(line.name())
+                } else {
+                    Some(line.short_name())
+                }
+            }
+            (None, designation) => designation,
+        }
+    }
+
+    /// Returns the direction text (RICHTUNG file) of the journey at the route entry, if any.
+    pub fn direction_at<'a>(
+        &'a self,
+        route_index: usize,
+        data_storage: &'a DataStorage,
+    ) -> Option<&'a str> {
+        let entry = self.direction_entry_at(route_index)?;
+        data_storage
+            .directions()
+            .find(entry.resource_id?)
+            .map(|direction| direction.name())
+    }
+
+    /// Returns the direction type (H or R) of the journey at the route entry, if any.
+    pub fn direction_type_at(&self, route_index: usize) -> Option<DirectionType> {
+        let entry = self.direction_entry_at(route_index)?;
+        DirectionType::from_str(entry.extra_field_1.as_deref()?).ok()
+    }
+
+    /// The direction can change along the route, the entry covering the route entry is used.
+    fn direction_entry_at(&self, route_index: usize) -> Option<&JourneyMetadataEntry> {
+        let position_of = |stop_id: Option<i32>| {
+            stop_id.and_then(|stop_id| {
+                self.route
+                    .iter()
+                    .position(|route_entry| route_entry.stop_id() == stop_id)
+            })
+        };
+
+        self.metadata()
+            .get(&JourneyMetadataType::Direction)?
+            .iter()
+            .find(|entry| {
+                let from = position_of(entry.from_stop_id).unwrap_or(0);
+                let until = position_of(entry.until_stop_id).unwrap_or(self.route.len());
+                from <= route_index && route_index <= until
+            })
+    }
+
+    /// Returns the planned platform (GLEIS file) of the journey at the route entry on the date, if any.
+    /// The date must correspond to the route's first entry.
+    /// The assignments restricted to a time or to a bit field take precedence over the general ones.
+    pub fn platform_at<'a>(
+        &self,
+        route_index: usize,
+        date: NaiveDate,
+        data_storage: &'a DataStorage,
+    ) -> Option<&'a Platform> {
+        let route_entry = self.route.get(route_index)?;
+
+        data_storage
+            .journey_platforms_by_journey_id()
+            .get(&self.id)?
+            .iter()
+            .filter_map(|&id| data_storage.journey_platform().find(id))
+            .filter(|journey_platform| {
+                journey_platform.is_valid_for(route_entry, date, data_storage)
+            })
+            .filter_map(|journey_platform| {
+                let platform = data_storage
+                    .platforms()
+                    .find(journey_platform.platform_id())?;
+                (platform.stop_id() == route_entry.stop_id())
+                    .then_some((journey_platform, platform))
+            })
+            .max_by_key(|(journey_platform, platform)| {
+                (
+                    journey_platform.time().is_some(),
+                    journey_platform.bit_field_id().is_some(),
+                    // Makes the result deterministic.
+                    std::cmp::Reverse(platform.id()),
+                )
+            })
+            .map(|(_, platform)| platform)
+    }
+
+    /// Same as platform_at, for the first route entry at the stop.
+    pub fn platform_of<'a>(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+        data_storage: &'a DataStorage,
+    ) -> Option<&'a Platform> {
+        let route_index = self
+            .route
+            .iter()
+            .position(|route_entry| route_entry.stop_id() == stop_id)?;
+        self.platform_at(route_index, date, data_storage)
+    }
+
     pub fn first_stop_id(&self) -> i32 {
         // unwrap: The route always contains at least 2 entries.
         self.route.first().unwrap().stop_id()
@@ -574,6 +1149,36 @@ impl Journey {
             + 1
     }
 
+    /// Returns the (arrival, departure) offsets in minutes of each stop of the route, relative to the departure at the first stop.
+    /// The offsets keep increasing after midnight.
+    pub fn stop_offsets(&self) -> Vec<(Option<i32>, Option<i32>)> {
+        let minutes_of_day = |time: NaiveTime| (time.num_seconds_from_midnight() / 60) as i32;
+        // unwrap: The first route entry always has a departure time.
+        let origin = minutes_of_day(self.route.first().unwrap().departure_time().unwrap());
+        let mut previous = 0;
+
+        let mut to_offset = |time: Option<NaiveTime>| {
+            time.map(|time| {
+                let mut offset = minutes_of_day(time) - origin;
+                // The times are stored modulo 24 hours.
+                while offset < previous {
+                    offset += 24 * 60;
+                }
+                previous = offset;
+                offset
+            })
+        };
+
+        self.route
+            .iter()
+            .map(|route_entry| {
+                let arrival = to_offset(*route_entry.arrival_time());
+                let departure = to_offset(*route_entry.departure_time());
+                (arrival, departure)
+            })
+            .collect()
+    }
+
     pub fn hash_route(&self, departure_stop_id: i32) -> Option<u64> {
         let index = self
             .route
@@ -684,32 +1289,283 @@ impl Journey {
         }
     }
 
-    /// Excluding departure stop.
-    pub fn route_section(
-        &self,
-        departure_stop_id: i32,
-        arrival_stop_id: i32,
-    ) -> Vec<&JourneyRouteEntry> {
-        let mut route_iter = self.route().iter();
-
-        for route_entry in route_iter.by_ref() {
-            if route_entry.stop_id() == departure_stop_id {
-                break;
-            }
-        }
-
-        let mut result = Vec::new();
+    /// Returns the timezone-aware (arrival, departure) date-times of each stop of the route.
+    /// The date is the service day, i.e. it must correspond to the route's first entry. The times of the route are local times.
+    /// In the repeated hour (end of daylight saving time), the date-times never go backwards along the route.
+    pub fn route_at_zoned(&self, date: NaiveDate, timezone: Tz) -> Vec<ZonedRouteEntry> {
+        // unwrap: The first route entry always has a departure time.
+        let origin =
+            NaiveDateTime::new(date, self.route.first().unwrap().departure_time().unwrap());
+        let mut previous = None;
+
+        let mut to_zoned = |offset: Option<i32>| {
+            offset.map(|offset| {
+                let result = to_timezone(
+                    origin + TimeDelta::minutes(offset.into()),
+                    timezone,
+                    previous,
+                );
+                previous = Some(result);
+                result
+            })
+        };
 
-        for route_entry in route_iter {
-            result.push(route_entry);
+        self.stop_offsets()
+            .into_iter()
+            .map(|(arrival, departure)| {
+                let arrival = to_zoned(arrival);
+                let departure = to_zoned(departure);
+                (arrival, departure)
+            })
+            .collect()
+    }
 
-            if route_entry.stop_id() == arrival_stop_id {
+    /// Timezone-aware variant of departure_at_of.
+    /// unwrap: Do not call this function if the stop is not part of the route.
+    /// unwrap: Do not call this function if the stop has no departure time (only the last stop has no departure time).
+    pub fn departure_at_of_zoned(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+        timezone: Tz,
+    ) -> DateTime<Tz> {
+        let index = self
+            .route
+            .iter()
+            .position(|route_entry| route_entry.stop_id() == stop_id)
+            .unwrap();
+        self.route_at_zoned(date, timezone)[index].1.unwrap()
+    }
+
+    /// Timezone-aware variant of arrival_time_of. The date must correspond to the route's first entry.
+    /// unwrap: Do not call this function if the stop is not part of the route.
+    /// unwrap: Do not call this function if the stop has no arrival time (only the first stop has no arrival time).
+    pub fn arrival_at_of_zoned(&self, stop_id: i32, date: NaiveDate, timezone: Tz) -> DateTime<Tz> {
+        let index = self
+            .route
+            .iter()
+            // The first route entry has no arrival time.
+            .skip(1)
+            .position(|route_entry| route_entry.stop_id() == stop_id)
+            .map(|i| i + 1)
+            .unwrap();
+        self.route_at_zoned(date, timezone)[index].0.unwrap()
+    }
+
+    /// Timezone-aware variant of departure_at_of_with_origin.
+    /// The date must be associated with the origin_stop_id.
+    pub fn departure_at_of_with_origin_zoned(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+        // If it's not a departure date, it's an arrival date.
+        is_departure_date: bool,
+        origin_stop_id: i32,
+        timezone: Tz,
+    ) -> DateTime<Tz> {
+        let date = self.service_date_of(date, is_departure_date, origin_stop_id);
+        self.departure_at_of_zoned(stop_id, date, timezone)
+    }
+
+    /// Timezone-aware variant of arrival_at_of_with_origin.
+    /// The date must be associated with the origin_stop_id.
+    pub fn arrival_at_of_with_origin_zoned(
+        &self,
+        stop_id: i32,
+        date: NaiveDate,
+        // If it's not a departure date, it's an arrival date.
+        is_departure_date: bool,
+        origin_stop_id: i32,
+        timezone: Tz,
+    ) -> DateTime<Tz> {
+        let date = self.service_date_of(date, is_departure_date, origin_stop_id);
+        self.arrival_at_of_zoned(stop_id, date, timezone)
+    }
+
+    /// Returns the elapsed minutes between the departure from a stop and the arrival at another stop,
+    /// taking the daylight saving time changes into account. The date must correspond to the route's first entry.
+    pub fn elapsed_minutes(
+        &self,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        date: NaiveDate,
+        timezone: Tz,
+    ) -> i64 {
+        let departure_at = self.departure_at_of_zoned(departure_stop_id, date, timezone);
+        let arrival_at = self.arrival_at_of_zoned(arrival_stop_id, date, timezone);
+        (arrival_at - departure_at).num_minutes()
+    }
+
+    /// Returns the date of the route's first entry, given a date associated with the origin_stop_id.
+    fn service_date_of(
+        &self,
+        date: NaiveDate,
+        is_departure_date: bool,
+        origin_stop_id: i32,
+    ) -> NaiveDate {
+        let (_, origin_is_next_day) = if is_departure_date {
+            self.departure_time_of(origin_stop_id)
+        } else {
+            self.arrival_time_of(origin_stop_id)
+        };
+
+        if origin_is_next_day {
+            sub_1_day(date)
+        } else {
+            date
+        }
+    }
+
+    /// Excluding departure stop.
+    pub fn route_section(
+        &self,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+    ) -> Vec<&JourneyRouteEntry> {
+        let mut route_iter = self.route().iter();
+
+        for route_entry in route_iter.by_ref() {
+            if route_entry.stop_id() == departure_stop_id {
+                break;
+            }
+        }
+
+        let mut result = Vec::new();
+
+        for route_entry in route_iter {
+            result.push(route_entry);
+
+            if route_entry.stop_id() == arrival_stop_id {
                 break;
             }
         }
 
         result
     }
+
+    /// Returns the information texts of the whole journey which are valid on the date, in the requested language.
+    /// The date must correspond to the route's first entry.
+    /// Information texts without content in the requested language are skipped.
+    pub fn information_texts<'a>(
+        &'a self,
+        data_storage: &'a DataStorage,
+        date: NaiveDate,
+        language: Language,
+    ) -> Vec<(InformationTextCode, &'a str)> {
+        // The route always contains at least 2 entries.
+        self.information_texts_between_indexes(
+            0,
+            self.route.len() - 1,
+            data_storage,
+            date,
+            language,
+        )
+    }
+
+    /// Same as information_texts, but only the texts applying to the section between the departure stop and the arrival stop are returned.
+    /// The date must correspond to the route's first entry.
+    pub fn information_texts_of_section<'a>(
+        &'a self,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        data_storage: &'a DataStorage,
+        date: NaiveDate,
+        language: Language,
+    ) -> Vec<(InformationTextCode, &'a str)> {
+        let Some(departure_index) = self.route_index_of(departure_stop_id, 0, None, false) else {
+            return Vec::new();
+        };
+        let Some(arrival_index) =
+            self.route_index_of(arrival_stop_id, departure_index + 1, None, true)
+        else {
+            return Vec::new();
+        };
+
+        self.information_texts_between_indexes(
+            departure_index,
+            arrival_index,
+            data_storage,
+            date,
+            language,
+        )
+    }
+
+    fn information_texts_between_indexes<'a>(
+        &'a self,
+        departure_index: usize,
+        arrival_index: usize,
+        data_storage: &'a DataStorage,
+        date: NaiveDate,
+        language: Language,
+    ) -> Vec<(InformationTextCode, &'a str)> {
+        let Some(entries) = self.metadata().get(&JourneyMetadataType::InformationText) else {
+            return Vec::new();
+        };
+
+        entries
+            .iter()
+            .filter(|entry| entry.is_valid_on(date, data_storage))
+            .filter(|entry| {
+                let (from_index, until_index) = self.route_range_of(entry);
+
+                if from_index == until_index {
+                    // The entry only applies to a single stop.
+                    departure_index <= from_index && from_index <= arrival_index
+                } else {
+                    from_index < arrival_index && departure_index < until_index
+                }
+            })
+            .filter_map(|entry| {
+                let information_text = data_storage
+                    .information_texts()
+                    .find(entry.resource_id()?)?;
+                let content = information_text.content(language)?;
+                Some((entry.information_text_code()?, content))
+            })
+            .collect()
+    }
+
+    /// Returns the indexes in the route of the first and the last stop to which the metadata entry applies.
+    fn route_range_of(&self, entry: &JourneyMetadataEntry) -> (usize, usize) {
+        let last_index = self.route.len() - 1;
+
+        let from_index = entry
+            .from_stop_id()
+            .and_then(|stop_id| self.route_index_of(stop_id, 0, entry.departure_time(), false))
+            .unwrap_or(0);
+        let until_index = entry
+            .until_stop_id()
+            .and_then(|stop_id| {
+                self.route_index_of(stop_id, from_index, entry.arrival_time(), true)
+            })
+            .unwrap_or(last_index);
+
+        (from_index, until_index)
+    }
+
+    /// The time, if provided, is used to distinguish the stops which are served more than once.
+    fn route_index_of(
+        &self,
+        stop_id: i32,
+        start_index: usize,
+        time: Option<NaiveTime>,
+        is_arrival: bool,
+    ) -> Option<usize> {
+        self.route
+            .iter()
+            .enumerate()
+            .skip(start_index)
+            .find(|(_, route_entry)| {
+                let route_entry_time = if is_arrival {
+                    *route_entry.arrival_time()
+                } else {
+                    *route_entry.departure_time()
+                };
+
+                route_entry.stop_id() == stop_id && (time.is_none() || time == route_entry_time)
+            })
+            .map(|(i, _)| i)
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -721,11 +1577,13 @@ pub enum JourneyMetadataType {
     #[default]
     Attribute,
     BitField,
+    BorderPoint,
     Direction,
     InformationText,
     Line,
     ExchangeTimeBoarding,
     ExchangeTimeDisembarking,
+    StopBitField,
     TransportType,
 }
 
@@ -768,6 +1626,52 @@ impl JourneyMetadataEntry {
             extra_field_2,
         }
     }
+
+    // Getters/Setters
+
+    pub fn from_stop_id(&self) -> Option<i32> {
+        self.from_stop_id
+    }
+
+    pub fn until_stop_id(&self) -> Option<i32> {
+        self.until_stop_id
+    }
+
+    pub fn resource_id(&self) -> Option<i32> {
+        self.resource_id
+    }
+
+    pub fn bit_field_id(&self) -> Option<i32> {
+        self.bit_field_id
+    }
+
+    pub fn departure_time(&self) -> Option<NaiveTime> {
+        self.departure_time
+    }
+
+    pub fn arrival_time(&self) -> Option<NaiveTime> {
+        self.arrival_time
+    }
+
+    // Functions
+
+    /// Only relevant for entries of type InformationText.
+    pub fn information_text_code(&self) -> Option<InformationTextCode> {
+        self.extra_field_1
+            .as_deref()
+            // unwrap: Parsing an InformationTextCode never fails, unknown codes end up in Other.
+            .map(|code| InformationTextCode::from_str(code).unwrap())
+    }
+
+    /// Returns true if the entry applies on the given date. An entry without bit field applies every day.
+    pub fn is_valid_on(&self, date: NaiveDate, data_storage: &DataStorage) -> bool {
+        match self.bit_field_id {
+            None => true,
+            Some(bit_field_id) => data_storage
+                .bit_fields_by_day()
+                .contains(date, bit_field_id),
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -779,6 +1683,8 @@ pub struct JourneyRouteEntry {
     stop_id: i32,
     arrival_time: Option<NaiveTime>,
     departure_time: Option<NaiveTime>,
+    boarding_allowed: bool, // A negative departure time means that boarding is not allowed.
+    alighting_allowed: bool, // A negative arrival time means that alighting is not allowed.
 }
 
 impl JourneyRouteEntry {
@@ -791,6 +1697,8 @@ impl JourneyRouteEntry {
             stop_id,
             arrival_time,
             departure_time,
+            boarding_allowed: true,
+            alighting_allowed: true,
         }
     }
 
@@ -808,6 +1716,22 @@ impl JourneyRouteEntry {
         &self.departure_time
     }
 
+    pub fn boarding_allowed(&self) -> bool {
+        self.boarding_allowed
+    }
+
+    pub fn set_boarding_allowed(&mut self, value: bool) {
+        self.boarding_allowed = value;
+    }
+
+    pub fn alighting_allowed(&self) -> bool {
+        self.alighting_allowed
+    }
+
+    pub fn set_alighting_allowed(&mut self, value: bool) {
+        self.alighting_allowed = value;
+    }
+
     // Functions
 
     pub fn stop<'a>(&'a self, data_storage: &'a DataStorage) -> &'a Stop {
@@ -824,15 +1748,21 @@ impl JourneyRouteEntry {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JourneyPlatform {
+    id: i32,
+    journey_id: i32, // The journey id already takes the administration (and the region for the TU code 801) into account.
     journey_legacy_id: i32,
     administration: String,
     platform_id: i32,
-    time: Option<NaiveTime>,
-    bit_field_id: Option<i32>,
+    time: Option<NaiveTime>, // The assignment only applies to the stop served at this time.
+    bit_field_id: Option<i32>, // The assignment only applies on the days of the bit field.
 }
 
+impl_Model!(JourneyPlatform);
+
 impl JourneyPlatform {
     pub fn new(
+        id: i32,
+        journey_id: i32,
         journey_legacy_id: i32,
         administration: String,
         platform_id: i32,
@@ -840,6 +1770,8 @@ impl JourneyPlatform {
         bit_field_id: Option<i32>,
     ) -> Self {
         Self {
+            id,
+            journey_id,
             journey_legacy_id,
             administration,
             platform_id,
@@ -847,13 +1779,52 @@ impl JourneyPlatform {
             bit_field_id,
         }
     }
-}
 
-impl Model<JourneyPlatform> for JourneyPlatform {
-    type K = (i32, i32);
+    // Getters/Setters
 
-    fn id(&self) -> Self::K {
-        (self.journey_legacy_id, self.platform_id)
+    pub fn journey_id(&self) -> i32 {
+        self.journey_id
+    }
+
+    pub fn journey_legacy_id(&self) -> i32 {
+        self.journey_legacy_id
+    }
+
+    pub fn administration(&self) -> &str {
+        &self.administration
+    }
+
+    pub fn platform_id(&self) -> i32 {
+        self.platform_id
+    }
+
+    pub fn time(&self) -> Option<NaiveTime> {
+        self.time
+    }
+
+    pub fn bit_field_id(&self) -> Option<i32> {
+        self.bit_field_id
+    }
+
+    // Functions
+
+    /// Returns true if the assignment applies to the route entry on the date.
+    /// The date must correspond to the route's first entry.
+    pub fn is_valid_for(
+        &self,
+        route_entry: &JourneyRouteEntry,
+        date: NaiveDate,
+        data_storage: &DataStorage,
+    ) -> bool {
+        let is_valid_at = self.time.is_none_or(|time| {
+            *route_entry.departure_time() == Some(time) || *route_entry.arrival_time() == Some(time)
+        });
+        let is_valid_on = self.bit_field_id.is_none_or(|bit_field_id| {
+            data_storage
+                .bit_fields_by_day()
+                .contains(date, bit_field_id)
+        });
+        is_valid_at && is_valid_on
     }
 }
 
@@ -909,10 +1880,22 @@ impl Line {
 
     // Getters/Setters
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn short_name(&self) -> &str {
+        &self.short_name
+    }
+
     pub fn set_short_name(&mut self, value: String) {
         self.short_name = value;
     }
 
+    pub fn long_name(&self) -> &str {
+        &self.long_name
+    }
+
     pub fn set_long_name(&mut self, value: String) {
         self.long_name = value;
     }
@@ -958,14 +1941,38 @@ impl Platform {
 
     // Getters/Setters
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn sectors(&self) -> Option<&str> {
+        self.sectors.as_deref()
+    }
+
+    pub fn stop_id(&self) -> i32 {
+        self.stop_id
+    }
+
+    pub fn sloid(&self) -> &str {
+        &self.sloid
+    }
+
     pub fn set_sloid(&mut self, value: String) {
         self.sloid = value;
     }
 
+    pub fn lv95_coordinates(&self) -> Coordinates {
+        self.lv95_coordinates
+    }
+
     pub fn set_lv95_coordinates(&mut self, value: Coordinates) {
         self.lv95_coordinates = value;
     }
 
+    pub fn wgs84_coordinates(&self) -> Coordinates {
+        self.wgs84_coordinates
+    }
+
     pub fn set_wgs84_coordinates(&mut self, value: Coordinates) {
         self.wgs84_coordinates = value;
     }
@@ -1091,6 +2098,7 @@ pub struct StopConnection {
     stop_id_2: i32,
     duration: i16, // Exchange time from stop 1 to stop 2 is in minutes.
     attribute: i32,
+    is_generated: bool, // True if the connection was generated from the coordinates of the stops, not read from METABHF.
 }
 
 impl_Model!(StopConnection);
@@ -1103,6 +2111,7 @@ impl StopConnection {
             stop_id_2,
             duration,
             attribute: 0,
+            is_generated: false,
         }
     }
 
@@ -1120,9 +2129,59 @@ impl StopConnection {
         self.duration
     }
 
+    /// The attribute (e.g. accessible, stairs) of the connection, None if it has none.
+    pub fn attribute_id(&self) -> Option<i32> {
+        (self.attribute != 0).then_some(self.attribute)
+    }
+
     pub fn set_attribute(&mut self, value: i32) {
         self.attribute = value;
     }
+
+    pub fn is_generated(&self) -> bool {
+        self.is_generated
+    }
+
+    pub fn set_generated(&mut self, value: bool) {
+        self.is_generated = value;
+    }
+
+    // Functions
+
+    pub fn attribute<'a>(&self, data_storage: &'a DataStorage) -> Option<&'a Attribute> {
+        data_storage.attributes().find(self.attribute_id()?)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- StopGroup
+// ------------------------------------------------------------------------------------------------
+
+/// Meta stop grouping several stops, e.g. all the stops of a city (METABHF file).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StopGroup {
+    id: i32, // Number of the meta stop.
+    stop_ids: Vec<i32>,
+}
+
+impl_Model!(StopGroup);
+
+impl StopGroup {
+    pub fn new(id: i32, stop_ids: Vec<i32>) -> Self {
+        Self { id, stop_ids }
+    }
+
+    // Getters/Setters
+
+    pub fn stop_ids(&self) -> &Vec<i32> {
+        &self.stop_ids
+    }
+
+    // Functions
+
+    pub fn add_stop_ids(&mut self, stop_ids: Vec<i32>) {
+        self.stop_ids.extend(stop_ids);
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -1181,6 +2240,64 @@ impl ThroughService {
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+// --- TimetableInfo
+// ------------------------------------------------------------------------------------------------
+
+/// Typed content of the ECKDATEN file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimetableInfo {
+    period: TimetablePeriod,
+    name: String,                      // E.g. "Fahrplan 2025".
+    created_at: Option<NaiveDateTime>, // Creation of the export, None if it cannot be read.
+    version: String,                   // Version of the HRDF export.
+    provider: String,                  // Producer of the HRDF export.
+}
+
+impl TimetableInfo {
+    pub fn new(
+        period: TimetablePeriod,
+        name: String,
+        created_at: Option<NaiveDateTime>,
+        version: String,
+        provider: String,
+    ) -> Self {
+        Self {
+            period,
+            name,
+            created_at,
+            version,
+            provider,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn period(&self) -> TimetablePeriod {
+        self.period
+    }
+
+    pub fn set_period(&mut self, value: TimetablePeriod) {
+        self.period = value;
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn created_at(&self) -> Option<NaiveDateTime> {
+        self.created_at
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // --- TimetableMetadataEntry
 // ------------------------------------------------------------------------------------------------
@@ -1216,6 +2333,49 @@ impl TimetableMetadataEntry {
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+// --- TimetablePeriod
+// ------------------------------------------------------------------------------------------------
+
+/// Validity period of the timetable (both dates included). The start date is the day 0 of the bit fields.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TimetablePeriod {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+}
+
+impl TimetablePeriod {
+    pub fn new(start_date: NaiveDate, end_date: NaiveDate) -> Result<Self, &'static str> {
+        if start_date > end_date {
+            return Err("The start date of the timetable period is after its end date.");
+        }
+        Ok(Self {
+            start_date,
+            end_date,
+        })
+    }
+
+    // Getters/Setters
+
+    pub fn start_date(&self) -> NaiveDate {
+        self.start_date
+    }
+
+    pub fn end_date(&self) -> NaiveDate {
+        self.end_date
+    }
+
+    // Functions
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && date <= self.end_date
+    }
+
+    pub fn num_days(&self) -> usize {
+        count_days_between_two_dates(self.start_date, self.end_date)
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // --- TransportCompany
 // ------------------------------------------------------------------------------------------------
@@ -1322,6 +2482,59 @@ impl TransportType {
     }
 }
 
+// ------------------------------------------------------------------------------------------------
+// --- TripPattern
+// ------------------------------------------------------------------------------------------------
+
+/// Group of journeys serving exactly the same ordered sequence of stops.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TripPattern {
+    id: i32,
+    stop_ids: Vec<i32>,
+    journey_ids: Vec<i32>, // Sorted by departure time at the first stop.
+    offsets: Vec<Vec<(Option<i32>, Option<i32>)>>, // For each journey (same order as journey_ids), the offsets of each stop. See Journey::stop_offsets.
+}
+
+impl_Model!(TripPattern);
+
+impl TripPattern {
+    pub fn new(
+        id: i32,
+        stop_ids: Vec<i32>,
+        journey_ids: Vec<i32>,
+        offsets: Vec<Vec<(Option<i32>, Option<i32>)>>,
+    ) -> Self {
+        Self {
+            id,
+            stop_ids,
+            journey_ids,
+            offsets,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn stop_ids(&self) -> &Vec<i32> {
+        &self.stop_ids
+    }
+
+    pub fn journey_ids(&self) -> &Vec<i32> {
+        &self.journey_ids
+    }
+
+    pub fn offsets(&self) -> &Vec<Vec<(Option<i32>, Option<i32>)>> {
+        &self.offsets
+    }
+
+    // Functions
+
+    /// Returns the offsets of each stop for the journey, None if the journey is not part of the pattern.
+    pub fn offsets_of(&self, journey_id: i32) -> Option<&Vec<(Option<i32>, Option<i32>)>> {
+        let index = self.journey_ids.iter().position(|&id| id == journey_id)?;
+        Some(&self.offsets[index])
+    }
+}
+
 // ------------------------------------------------------------------------------------------------
 // --- Version
 // ------------------------------------------------------------------------------------------------
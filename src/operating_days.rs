@@ -0,0 +1,404 @@
+// ------------------------------------------------------------------------------------------------
+// --- Operating days description
+// ------------------------------------------------------------------------------------------------
+
+use chrono::{Datelike, NaiveDate};
+use rustc_hash::FxHashMap;
+
+use crate::models::{BitField, Holiday, Language};
+
+/// Returns a passenger-facing description of the days on which the bit field is active,
+/// e.g. "Mon–Fri, not on 25 Dec (Christmas Day), 1 Jan (New Year's Day)".
+///
+/// The description consists of a weekday pattern followed by its exceptions. A weekday is part of the pattern
+/// if the bit field is active on more than half of its dates, which keeps the number of exceptions low.
+/// At least 3 exceptions in a row are merged into a range, e.g. "only on 1 Jun–31 Aug" for a seasonal service.
+/// The days which don't belong to the weekday pattern don't break a range of exceptions of the pattern (and vice versa).
+/// The exceptions falling on a holiday are followed by the name of the holiday.
+/// The timetable period is given by timetable_start_date and timetable_end_date.
+pub fn describe_operating_days(
+    bit_field: &BitField,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    holidays: &[&Holiday],
+    language: Language,
+) -> String {
+    let texts = texts(language);
+    let dates: Vec<(NaiveDate, bool)> = start_date
+        .iter_days()
+        .take_while(|&date| date <= end_date)
+        .map(|date| (date, bit_field.is_active_on(date, start_date, end_date)))
+        .collect();
+
+    if dates.iter().all(|&(_, is_active)| !is_active) {
+        return texts.no_service.to_string();
+    }
+
+    // For each weekday: (number of active dates, number of dates).
+    let mut counts = [(0, 0); 7];
+    for &(date, is_active) in &dates {
+        let count = &mut counts[weekday_index(date)];
+        count.1 += 1;
+        if is_active {
+            count.0 += 1;
+        }
+    }
+    let weekdays: [bool; 7] = std::array::from_fn(|i| counts[i].0 * 2 > counts[i].1);
+
+    let not_on: Vec<NaiveDate> = dates
+        .iter()
+        .filter(|&&(date, is_active)| weekdays[weekday_index(date)] && !is_active)
+        .map(|&(date, _)| date)
+        .collect();
+    let also_on: Vec<NaiveDate> = dates
+        .iter()
+        .filter(|&&(date, is_active)| !weekdays[weekday_index(date)] && is_active)
+        .map(|&(date, _)| date)
+        .collect();
+
+    let holiday_names: FxHashMap<NaiveDate, &str> = holidays
+        .iter()
+        .filter_map(|holiday| Some((holiday.date(), holiday.name(language)?)))
+        .collect();
+    // Without the year, the dates would be ambiguous if the period is longer than a year.
+    let with_year = dates.len() > 365;
+    let format_dates = |dates: &[NaiveDate], is_in_pattern: bool| {
+        let is_neutral = |date| weekdays[weekday_index(date)] != is_in_pattern;
+        group_dates(dates, is_neutral)
+            .into_iter()
+            .flat_map(|group| {
+                if group.len() >= 3 {
+                    // unwrap: A group always contains at least 1 date.
+                    let (first, last) = (group[0], *group.last().unwrap());
+                    vec![format!(
+                        "{}–{}",
+                        format_date(first, with_year, &texts),
+                        format_date(last, with_year, &texts)
+                    )]
+                } else {
+                    group
+                        .into_iter()
+                        .map(|date| {
+                            let formatted = format_date(date, with_year, &texts);
+                            match holiday_names.get(&date) {
+                                Some(name) => format!("{formatted} ({name})"),
+                                None => formatted,
+                            }
+                        })
+                        .collect()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut parts = Vec::new();
+    if weekdays.iter().any(|&is_included| is_included) {
+        parts.push(format_weekdays(&weekdays, &texts));
+        if !not_on.is_empty() {
+            parts.push(format!("{} {}", texts.not_on, format_dates(&not_on, true)));
+        }
+        if !also_on.is_empty() {
+            parts.push(format!(
+                "{} {}",
+                texts.also_on,
+                format_dates(&also_on, false)
+            ));
+        }
+    } else {
+        parts.push(format!(
+            "{} {}",
+            texts.only_on,
+            format_dates(&also_on, false)
+        ));
+    }
+    parts.join(", ")
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+struct Texts {
+    daily: &'static str,
+    no_service: &'static str,
+    not_on: &'static str,
+    also_on: &'static str,
+    only_on: &'static str,
+    weekdays: [&'static str; 7], // Starting on Monday.
+    months: [&'static str; 12],
+    day_suffix: &'static str, // Written after the day of the month, e.g. "25." in German.
+}
+
+fn texts(language: Language) -> Texts {
+    match language {
+        Language::German => Texts {
+            daily: "täglich",
+            no_service: "verkehrt nicht",
+            not_on: "nicht am",
+            also_on: "auch am",
+            only_on: "nur am",
+            weekdays: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+            months: [
+                "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+            ],
+            day_suffix: ".",
+        },
+        Language::French => Texts {
+            daily: "tous les jours",
+            no_service: "ne circule pas",
+            not_on: "sauf le",
+            also_on: "aussi le",
+            only_on: "seulement le",
+            weekdays: ["lu", "ma", "me", "je", "ve", "sa", "di"],
+            months: [
+                "janv", "févr", "mars", "avr", "mai", "juin", "juil", "août", "sept", "oct", "nov",
+                "déc",
+            ],
+            day_suffix: "",
+        },
+        Language::Italian => Texts {
+            daily: "giornaliero",
+            no_service: "non circola",
+            not_on: "eccetto il",
+            also_on: "anche il",
+            only_on: "solo il",
+            weekdays: ["lu", "ma", "me", "gi", "ve", "sa", "do"],
+            months: [
+                "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+            ],
+            day_suffix: "",
+        },
+        Language::English => Texts {
+            daily: "daily",
+            no_service: "no service",
+            not_on: "not on",
+            also_on: "also on",
+            only_on: "only on",
+            weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+            months: [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+            ],
+            day_suffix: "",
+        },
+    }
+}
+
+fn weekday_index(date: NaiveDate) -> usize {
+    date.weekday().num_days_from_monday() as usize
+}
+
+/// Consecutive weekdays are grouped when there are at least 3 of them, e.g. "Mon–Fri" or "Sat, Sun".
+fn format_weekdays(weekdays: &[bool; 7], texts: &Texts) -> String {
+    if weekdays.iter().all(|&is_included| is_included) {
+        return texts.daily.to_string();
+    }
+
+    let mut groups = Vec::new();
+    let mut i = 0;
+    while i < 7 {
+        if !weekdays[i] {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < 7 && weekdays[i] {
+            i += 1;
+        }
+
+        if i - start >= 3 {
+            groups.push(format!(
+                "{}–{}",
+                texts.weekdays[start],
+                texts.weekdays[i - 1]
+            ));
+        } else {
+            groups.extend((start..i).map(|j| texts.weekdays[j].to_string()));
+        }
+    }
+    groups.join(", ")
+}
+
+/// Groups the sorted dates which follow each other. A date missing between two dates doesn't break the group if it is neutral.
+fn group_dates(dates: &[NaiveDate], is_neutral: impl Fn(NaiveDate) -> bool) -> Vec<Vec<NaiveDate>> {
+    let mut groups: Vec<Vec<NaiveDate>> = Vec::new();
+    for &date in dates {
+        let follows = groups
+            .last()
+            .and_then(|group| group.last())
+            .is_some_and(|previous| {
+                previous
+                    .iter_days()
+                    .skip(1)
+                    .take_while(|&day| day < date)
+                    .all(&is_neutral)
+            });
+        match groups.last_mut() {
+            Some(group) if follows => group.push(date),
+            _ => groups.push(vec![date]),
+        }
+    }
+    groups
+}
+
+fn format_date(date: NaiveDate, with_year: bool, texts: &Texts) -> String {
+    let month = texts.months[date.month0() as usize];
+    let formatted = format!("{}{} {}", date.day(), texts.day_suffix, month);
+    if with_year {
+        format!("{formatted} {}", date.year())
+    } else {
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BIT_FIELD_WORDS;
+    use pretty_assertions::assert_eq;
+
+    fn create_bit_field(start_date: NaiveDate, dates: &[NaiveDate]) -> BitField {
+        let mut words = [0u64; BIT_FIELD_WORDS];
+        for date in dates {
+            // The first two bits are ignored.
+            let bit = usize::try_from((*date - start_date).num_days()).unwrap() + 2;
+            words[bit / 64] |= 1 << (bit % 64);
+        }
+        BitField::new(1, words)
+    }
+
+    fn create_holiday(id: i32, date: NaiveDate, german: &str, english: &str) -> Holiday {
+        let name = FxHashMap::from_iter([
+            (Language::German, german.to_string()),
+            (Language::English, english.to_string()),
+        ]);
+        Holiday::new(id, date, name)
+    }
+
+    #[test]
+    fn describe_operating_days_with_exceptions() {
+        // From Monday 16 Dec 2024 to Sunday 19 Jan 2025 (5 weeks).
+        let start_date = NaiveDate::from_ymd_opt(2024, 12, 16).unwrap();
+        let end_date = NaiveDate::from_ymd_opt(2025, 1, 19).unwrap();
+        let christmas = NaiveDate::from_ymd_opt(2024, 12, 25).unwrap();
+        let new_year = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let saturday = NaiveDate::from_ymd_opt(2024, 12, 28).unwrap();
+
+        let dates: Vec<_> = start_date
+            .iter_days()
+            .take_while(|&date| date <= end_date)
+            .filter(|&date| {
+                (weekday_index(date) < 5 && date != christmas && date != new_year)
+                    || date == saturday
+            })
+            .collect();
+        let bit_field = create_bit_field(start_date, &dates);
+        let holidays = [
+            create_holiday(1, christmas, "Weihnachtstag", "Christmas Day"),
+            create_holiday(2, new_year, "Neujahrstag", "New Year's Day"),
+        ];
+        let holidays: Vec<_> = holidays.iter().collect();
+
+        assert_eq!(
+            "Mon–Fri, not on 25 Dec (Christmas Day), 1 Jan (New Year's Day), also on 28 Dec",
+            describe_operating_days(
+                &bit_field,
+                start_date,
+                end_date,
+                &holidays,
+                Language::English
+            )
+        );
+        assert_eq!(
+            "Mo–Fr, nicht am 25. Dez (Weihnachtstag), 1. Jan (Neujahrstag), auch am 28. Dez",
+            describe_operating_days(
+                &bit_field,
+                start_date,
+                end_date,
+                &holidays,
+                Language::German
+            )
+        );
+        // No holiday name in French, only the date is given.
+        assert_eq!(
+            "lu–ve, sauf le 25 déc, 1 janv, aussi le 28 déc",
+            describe_operating_days(
+                &bit_field,
+                start_date,
+                end_date,
+                &holidays,
+                Language::French
+            )
+        );
+    }
+
+    #[test]
+    fn describe_operating_days_special_cases() {
+        let start_date = NaiveDate::from_ymd_opt(2024, 12, 16).unwrap();
+        let end_date = NaiveDate::from_ymd_opt(2024, 12, 29).unwrap();
+        let all_dates: Vec<_> = start_date.iter_days().take(14).collect();
+        let describe = |dates: &[NaiveDate]| {
+            let bit_field = create_bit_field(start_date, dates);
+            describe_operating_days(&bit_field, start_date, end_date, &[], Language::English)
+        };
+
+        assert_eq!("daily", describe(&all_dates));
+        assert_eq!("no service", describe(&[]));
+        assert_eq!(
+            "Sat, Sun",
+            describe(
+                &all_dates[5..7]
+                    .iter()
+                    .chain(&all_dates[12..14])
+                    .copied()
+                    .collect::<Vec<_>>()
+            )
+        );
+        assert_eq!("only on 18 Dec", describe(&all_dates[2..3]));
+    }
+
+    #[test]
+    fn describe_operating_days_with_ranges() {
+        let start_date = NaiveDate::from_ymd_opt(2024, 12, 15).unwrap();
+        let end_date = NaiveDate::from_ymd_opt(2025, 12, 13).unwrap();
+        let date = |month, day| NaiveDate::from_ymd_opt(2025, month, day).unwrap();
+        let all_dates: Vec<_> = start_date
+            .iter_days()
+            .take_while(|&date| date <= end_date)
+            .collect();
+        let describe = |dates: Vec<NaiveDate>| {
+            let bit_field = create_bit_field(start_date, &dates);
+            describe_operating_days(&bit_field, start_date, end_date, &[], Language::English)
+        };
+
+        // A seasonal service.
+        let summer = all_dates
+            .iter()
+            .copied()
+            .filter(|&d| date(6, 1) <= d && d <= date(8, 31));
+        assert_eq!("only on 1 Jun–31 Aug", describe(summer.collect()));
+
+        // The weekends don't break the school holidays (Monday 7 Jul to Friday 15 Aug), 2 days in a row are not merged.
+        let school_days = all_dates.iter().copied().filter(|&d| {
+            weekday_index(d) < 5
+                && !(date(7, 7) <= d && d <= date(8, 15))
+                && d != date(4, 17)
+                && d != date(4, 18)
+        });
+        assert_eq!(
+            "Mon–Fri, not on 17 Apr, 18 Apr, 7 Jul–15 Aug",
+            describe(school_days.collect())
+        );
+
+        // The weekdays don't break the weekend days on which the service also operates.
+        let weekdays_and_december = all_dates
+            .iter()
+            .copied()
+            .filter(|&d| weekday_index(d) < 5 || (date(12, 1) <= d && d <= date(12, 13)));
+        assert_eq!(
+            "Mon–Fri, also on 6 Dec–13 Dec",
+            describe(weekdays_and_december.collect())
+        );
+    }
+}
@@ -1,5 +1,6 @@
 mod attribute_parser;
 mod bit_field_parser;
+mod border_point_parser;
 mod direction_parser;
 mod exchange_administration_parser;
 mod exchange_journey_parser;
@@ -23,6 +24,7 @@ mod description_parser;
 
 pub use attribute_parser::parse as load_attributes;
 pub use bit_field_parser::parse as load_bit_fields;
+pub use border_point_parser::parse as load_border_points;
 pub use direction_parser::parse as load_directions;
 pub use exchange_administration_parser::parse as load_exchange_times_administration;
 pub use exchange_journey_parser::parse as load_exchange_times_journey;
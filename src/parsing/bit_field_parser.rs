@@ -32,7 +32,7 @@ use nom::sequence::preceded;
 use rustc_hash::FxHashMap;
 
 use crate::{
-    models::{BitField, Model},
+    models::{BIT_FIELD_WORDS, BitField, Model},
     parsing::{ColumnDefinition, ExpectedType, FileParser, ParsedValue, RowDefinition, RowParser},
     storage::ResourceStorage,
 };
@@ -88,8 +88,8 @@ fn row_converter(parser: FileParser) -> Result<FxHashMap<i32, BitField>, Box<dyn
 
 fn create_instance(values: Vec<ParsedValue>) -> Result<BitField, Box<dyn Error>> {
     let (id, hex_number) = row_from_parsed_values(values);
-    let bits = convert_hex_number_to_bits(hex_number)?;
-    Ok(BitField::new(id, bits))
+    let words = convert_hex_number_to_words(hex_number)?;
+    Ok(BitField::new(id, words))
 }
 
 pub fn parse(path: &str) -> Result<ResourceStorage<BitField>, Box<dyn Error>> {
@@ -110,28 +110,34 @@ fn row_from_parsed_values(mut values: Vec<ParsedValue>) -> (i32, String) {
 // --- Helper Functions
 // ------------------------------------------------------------------------------------------------
 
-/// Converts a hexadecimal number into a list of where each item represents a bit.
-fn convert_hex_number_to_bits(hex_number: String) -> Result<Vec<u8>, Box<dyn Error>> {
-    let result = hex_number
-        .chars()
-        .map(|hex_digit| {
-            hex_digit
-                .to_digit(16)
-                .ok_or("Invalid hexadecimal digit")
-                .map(|val| (0..4).rev().map(move |i| ((val >> i) & 1) as u8))
-        })
-        .collect::<Result<Vec<_>, _>>()?
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
-    Ok(result)
+/// Converts a hexadecimal number into packed bits. The first (most significant) bit of the hexadecimal number is the bit 0.
+fn convert_hex_number_to_words(
+    hex_number: String,
+) -> Result<[u64; BIT_FIELD_WORDS], Box<dyn Error>> {
+    let mut words = [0u64; BIT_FIELD_WORDS];
+
+    for (i, hex_digit) in hex_number.chars().enumerate() {
+        let val = hex_digit.to_digit(16).ok_or("Invalid hexadecimal digit")?;
+        for j in 0..4 {
+            if (val >> (3 - j)) & 1 == 1 {
+                let bit = i * 4 + j;
+                let word = words
+                    .get_mut(bit / 64)
+                    .ok_or("Hexadecimal number too long")?;
+                *word |= 1 << (bit % 64);
+            }
+        }
+    }
+    Ok(words)
 }
 
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use crate::models::BitFieldsByDay;
     use crate::parsing::tests::get_json_values;
+    use chrono::{Datelike, Days, NaiveDate};
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -179,7 +185,7 @@ mod tests {
         let reference = r#"
             {
                 "id": 17,
-                "bits": [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+                "words": [18446744073709551615, 18446744073709551615, 18446744073709551615, 18446744073709551615, 18446744073709551615, 281474976710655]
             }"#;
         let (attribute, reference) = get_json_values(attribute, reference).unwrap();
         assert_eq!(attribute, reference);
@@ -188,9 +194,125 @@ mod tests {
         let reference = r#"
             {
                 "id": 425152,
-                "bits": [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 0, 1, 0, 1, 1, 1, 1, 0, 1, 0, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 0, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+                "words": [18446744039349813247, 18445334413231251455, 18445615940419715069, 18446744073709551615, 18446744073709551615, 281474976710655]
             }"#;
         let (attribute, reference) = get_json_values(attribute, reference).unwrap();
         assert_eq!(attribute, reference);
     }
+
+    #[test]
+    fn calendar_v207() {
+        let rows = vec![
+            "000001 F80000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000".to_string(),
+            "000002 A80000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000".to_string(),
+        ];
+        let bitfield_parser = BitFieldParser::new();
+        let parser = FileParser {
+            row_parser: bitfield_parser.row_parser.clone(),
+            rows,
+        };
+        let data = row_converter(parser).unwrap();
+        let (bit_field_1, bit_field_2) = (data.get(&1).unwrap(), data.get(&2).unwrap());
+        let start_date = NaiveDate::from_ymd_opt(2024, 12, 15).unwrap();
+        let end_date = NaiveDate::from_ymd_opt(2024, 12, 20).unwrap();
+        let dates = |bit_field: &BitField| {
+            bit_field
+                .operating_dates(start_date, end_date)
+                .map(|date| date.day())
+                .collect::<Vec<_>>()
+        };
+
+        // The first two bits are ignored.
+        assert_eq!(vec![15, 16, 17], dates(bit_field_1));
+        assert_eq!(vec![15, 17], dates(bit_field_2));
+        assert_eq!(2, bit_field_2.count_operating_days(start_date, end_date));
+        assert!(bit_field_2.is_active_on(start_date, start_date, end_date));
+        assert!(!bit_field_2.is_active_on(
+            NaiveDate::from_ymd_opt(2024, 12, 16).unwrap(),
+            start_date,
+            end_date
+        ));
+        assert!(!bit_field_1.is_active_on(
+            NaiveDate::from_ymd_opt(2024, 12, 14).unwrap(),
+            start_date,
+            end_date
+        ));
+
+        assert_eq!(vec![15, 16, 17], dates(&bit_field_1.union(bit_field_2, 3)));
+        assert_eq!(
+            vec![15, 17],
+            dates(&bit_field_1.intersection(bit_field_2, 4))
+        );
+        assert_eq!(vec![16], dates(&bit_field_1.difference(bit_field_2, 5)));
+
+        // The days 1 and 2 (16 and 17 December) become the days 0 and 1.
+        let rebased = bit_field_2.rebased(1, 2);
+        assert_eq!(vec![16], dates(&rebased));
+        assert!(!rebased.is_empty());
+        assert!(bit_field_2.rebased(1, 1).is_empty());
+    }
+
+    #[test]
+    fn last_word_v207() {
+        // The bit 383 is the last bit of the last word, it is the day 381.
+        let hex_number = format!("{}1", "0".repeat(95));
+        let words = convert_hex_number_to_words(hex_number).unwrap();
+        assert_eq!(1 << 63, words[BIT_FIELD_WORDS - 1]);
+
+        let bit_field = BitField::new(1, words);
+        assert!(bit_field.contains(381));
+        assert!(!bit_field.contains(380));
+        assert!(!bit_field.contains(382));
+
+        // A bit after the last word is an error.
+        assert!(convert_hex_number_to_words(format!("{}1", "0".repeat(96))).is_err());
+    }
+
+    #[test]
+    fn bit_fields_by_day_v207() {
+        // The bit field 1 is active on the days 0, 1 and 2, the bit field 2 on the days 0 and 2.
+        // The bit fields 3 to 70 are active on the day 0, which takes 2 words per day. The bit field 70 is also active on the day 381.
+        let mut rows = vec![
+            format!("000001 F8{}", "0".repeat(94)),
+            format!("000002 A8{}", "0".repeat(94)),
+        ];
+        rows.extend((3..70).map(|id| format!("{id:06} 2{}", "0".repeat(95))));
+        rows.push(format!("000070 2{}1", "0".repeat(94)));
+        let bitfield_parser = BitFieldParser::new();
+        let parser = FileParser {
+            row_parser: bitfield_parser.row_parser.clone(),
+            rows,
+        };
+        let data = row_converter(parser).unwrap();
+        let start_date = NaiveDate::from_ymd_opt(2024, 12, 15).unwrap();
+        let end_date = start_date.checked_add_days(Days::new(381)).unwrap();
+        let bit_fields_by_day = BitFieldsByDay::new(data.values().collect(), start_date, end_date);
+        let date = |day| start_date.checked_add_days(Days::new(day)).unwrap();
+
+        assert!(bit_fields_by_day.contains(date(1), 1));
+        assert!(!bit_fields_by_day.contains(date(1), 2));
+        assert!(bit_fields_by_day.contains(date(0), 70));
+        assert!(bit_fields_by_day.contains(end_date, 70));
+        assert!(!bit_fields_by_day.contains(end_date, 69));
+        // Unknown bit field.
+        assert!(!bit_fields_by_day.contains(date(0), 71));
+
+        assert_eq!(
+            (0..=70).collect::<Vec<_>>(),
+            bit_fields_by_day.bit_field_ids_on(date(0))
+        );
+        assert_eq!(vec![0, 1], bit_fields_by_day.bit_field_ids_on(date(1)));
+        assert_eq!(vec![0, 1, 2], bit_fields_by_day.bit_field_ids_on(date(2)));
+        assert_eq!(vec![0], bit_fields_by_day.bit_field_ids_on(date(3)));
+        assert_eq!(vec![0, 70], bit_fields_by_day.bit_field_ids_on(end_date));
+
+        // The bit field 0 is active on every day of the timetable period, and no bit field outside of it.
+        assert!(bit_fields_by_day.contains(date(3), 0));
+        let before = start_date.pred_opt().unwrap();
+        let after = end_date.succ_opt().unwrap();
+        assert!(!bit_fields_by_day.contains(before, 0));
+        assert!(!bit_fields_by_day.contains(after, 1));
+        assert!(bit_fields_by_day.bit_field_ids_on(before).is_empty());
+        assert!(bit_fields_by_day.bit_field_ids_on(after).is_empty());
+    }
 }
@@ -0,0 +1,177 @@
+/// # Border point parsing
+///
+/// For more informations see
+/// [https://opentransportdata.swiss/en/cookbook/hafas-rohdaten-format-hrdf/#Technical_description_What_is_in_the_HRDF_files_contents](the HRDF documentation).
+///
+/// List of the border points, i.e. the stops at which a journey crosses a (tariff) border.
+/// The *GR lines of the FPLAN file refer to these border points.
+///
+/// It includes:
+///     - The stop number of the border point
+///     - (optional) The designation of the border point
+///
+/// ### Example (excerpt):
+///
+/// `
+/// 8500090 Basel Bad Bf % Grenzpunkt HS-Nr. 8500090 (Basel Bad Bf)
+/// `
+///
+/// Can be read in decoupled from other data. The file is optional: if it is missing, there are no border points.
+///
+/// 1 file(s).
+/// File(s) read by the parser:
+/// GRENZHLT
+use std::{error::Error, path::Path};
+use nom::bytes::complete::take;
+use nom::character::complete::space1;
+use nom::combinator::{opt, rest};
+use nom::Parser;
+use nom::sequence::preceded;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    models::{BorderPoint, Model},
+    parsing::{ColumnDefinition, ExpectedType, FileParser, ParsedValue, RowDefinition, RowParser},
+    storage::ResourceStorage,
+};
+use crate::parsing::ParserFnReturn;
+
+pub struct BorderPointParser {
+    file: String,
+    row_parser: RowParser
+}
+
+impl BorderPointParser {
+    fn get_parser_1(input: &str) -> ParserFnReturn<'_> {
+        let mut parser = (
+            take(7usize),
+            opt(preceded(space1, rest))
+        );
+        let (i2, data) = parser.parse(input)?;
+        Ok((i2, vec![data.0, data.1.unwrap_or("")]))
+    }
+
+    pub fn new() -> Self {
+        Self {
+            file: "GRENZHLT".to_string(),
+            row_parser: RowParser::new({
+                let mut rows = vec![];
+                rows.push(RowDefinition::new(
+                    0,
+                    vec![
+                        ColumnDefinition::new(ExpectedType::Integer32),
+                        ColumnDefinition::new(ExpectedType::String),
+                    ],
+                    Self::get_parser_1
+                ));
+                rows
+            })
+        }
+    }
+
+    fn parse(&self, path: &str) -> Result<ResourceStorage<BorderPoint>, Box<dyn Error>> {
+        let file_path = format!("{}/{}", path, self.file);
+        // The file is optional, some exports have no border points.
+        if !Path::new(&file_path).exists() {
+            log::info!("{} not found, no border points.", self.file);
+            return Ok(ResourceStorage::new(FxHashMap::default()));
+        }
+
+        log::info!("Parsing {}...", self.file);
+        let parser = FileParser::new(&file_path, self.row_parser.clone())?;
+        let data = row_converter(parser)?;
+
+        Ok(ResourceStorage::new(data))
+    }
+}
+
+fn row_converter(parser: FileParser) -> Result<FxHashMap<i32, BorderPoint>, Box<dyn Error>> {
+    let data = parser
+        .parse()
+        .map(|x| x.map(|(_, _, values)| create_instance(values)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let data = BorderPoint::vec_to_map(data);
+    Ok(data)
+}
+
+fn create_instance(values: Vec<ParsedValue>) -> BorderPoint {
+    let (stop_id, designation) = row_from_parsed_values(values);
+    // The designation is optional, the stop name can be used instead.
+    let designation = (!designation.is_empty()).then_some(designation);
+    BorderPoint::new(stop_id, designation)
+}
+
+pub fn parse(path: &str) -> Result<ResourceStorage<BorderPoint>, Box<dyn Error>> {
+    BorderPointParser::new().parse(path)
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Data Processing Functions
+// ------------------------------------------------------------------------------------------------
+
+fn row_from_parsed_values(mut values: Vec<ParsedValue>) -> (i32, String) {
+    let stop_id: i32 = values.remove(0).into();
+    let designation: String = values.remove(0).into();
+    (stop_id, designation)
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::parsing::tests::get_json_values;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn row_parser_v207() {
+        let rows = vec!["8500090 Basel Bad Bf".to_string(), "8014441".to_string()];
+        let border_point_parser = BorderPointParser::new();
+        let parser = FileParser {
+            row_parser: border_point_parser.row_parser.clone(),
+            rows,
+        };
+        let mut parser_iterator = parser.parse();
+        let (_, _, parsed_values) = parser_iterator.next().unwrap().unwrap();
+        let (stop_id, designation) = row_from_parsed_values(parsed_values);
+        assert_eq!(8500090, stop_id);
+        assert_eq!("Basel Bad Bf", &designation);
+        let (_, _, parsed_values) = parser_iterator.next().unwrap().unwrap();
+        let (stop_id, designation) = row_from_parsed_values(parsed_values);
+        assert_eq!(8014441, stop_id);
+        assert_eq!("", &designation);
+    }
+
+    #[test]
+    fn type_converter_v207() {
+        let rows = vec!["8500090 Basel Bad Bf".to_string(), "8014441".to_string()];
+        let border_point_parser = BorderPointParser::new();
+        let parser = FileParser {
+            row_parser: border_point_parser.row_parser.clone(),
+            rows,
+        };
+        let data = row_converter(parser).unwrap();
+        let attribute = data.get(&8500090).unwrap();
+        let reference = r#"
+            {
+                "id": 8500090,
+                "designation": "Basel Bad Bf"
+            }"#;
+        let (attribute, reference) = get_json_values(attribute, reference).unwrap();
+        assert_eq!(attribute, reference);
+        let attribute = data.get(&8014441).unwrap();
+        let reference = r#"
+            {
+                "id": 8014441,
+                "designation": null
+            }"#;
+        let (attribute, reference) = get_json_values(attribute, reference).unwrap();
+        assert_eq!(attribute, reference);
+    }
+
+    #[test]
+    fn missing_file_v207() {
+        let path = std::env::temp_dir().join("hrdf_parser_missing_grenzhlt");
+        let data = parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(0, data.entries().len());
+    }
+}
@@ -30,11 +30,11 @@ use nom::character::complete::{char, space1};
 use nom::combinator::opt;
 use nom::Parser;
 use nom::sequence::preceded;
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::FxHashMap;
 
 use crate::{
-    JourneyId,
-    models::{ExchangeTimeJourney, Model},
+    models::{ExchangeTimeJourney, Journey, Model},
+    parsing::journey_parser::{JourneysPkTypeConverter, resolve_journey_id},
     parsing::{ColumnDefinition, ExpectedType, FileParser, ParsedValue, RowDefinition, RowParser},
     storage::ResourceStorage,
     utils::AutoIncrement,
@@ -89,27 +89,35 @@ impl ExchangeTimeJourneyParser {
     fn parse(
         &self,
         path: &str,
-        journeys_pk_type_converter: &FxHashSet<JourneyId>,
+        journeys_pk_type_converter: &JourneysPkTypeConverter,
+        journeys: &ResourceStorage<Journey>,
     ) -> Result<ResourceStorage<ExchangeTimeJourney>, Box<dyn Error>> {
         log::info!("Parsing {}...", self.file);
         let parser = FileParser::new(&format!("{}/{}", path, self.file), self.row_parser.clone())?;
-        let data = row_converter(parser, journeys_pk_type_converter)?;
+        let data = row_converter(parser, journeys_pk_type_converter, journeys)?;
         Ok(ResourceStorage::new(data))
     }
 }
 
 fn row_converter(
     parser: FileParser,
-    journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    journeys_pk_type_converter: &JourneysPkTypeConverter,
+    journeys: &ResourceStorage<Journey>,
 ) -> Result<FxHashMap<i32, ExchangeTimeJourney>, Box<dyn Error>> {
     let auto_increment = AutoIncrement::new();
 
     let data = parser
         .parse()
-        .map(|x| {
+        .filter_map(|x| {
             x.and_then(|(_, _, values)| {
-                create_instance(values, &auto_increment, journeys_pk_type_converter)
+                create_instance(
+                    values,
+                    &auto_increment,
+                    journeys_pk_type_converter,
+                    journeys,
+                )
             })
+            .transpose()
         })
         .collect::<Result<Vec<_>, _>>()?;
     let data = ExchangeTimeJourney::vec_to_map(data);
@@ -119,8 +127,9 @@ fn row_converter(
 fn create_instance(
     mut values: Vec<ParsedValue>,
     auto_increment: &AutoIncrement,
-    journeys_pk_type_converter: &FxHashSet<JourneyId>,
-) -> Result<ExchangeTimeJourney, Box<dyn Error>> {
+    journeys_pk_type_converter: &JourneysPkTypeConverter,
+    journeys: &ResourceStorage<Journey>,
+) -> Result<Option<ExchangeTimeJourney>, Box<dyn Error>> {
     let stop_id: i32 = values.remove(0).into();
     let journey_id_1: i32 = values.remove(0).into();
     let administration_1: String = values.remove(0).into();
@@ -130,35 +139,54 @@ fn create_instance(
     let is_guaranteed: String = values.remove(0).into();
     let bit_field_id: Option<i32> = values.remove(0).into();
 
-    let _journey_id_1 = journeys_pk_type_converter
-        .get(&(journey_id_1, administration_1.clone()))
-        .ok_or("Unknown legacy ID")?;
+    let journey_legacy_id_1 = (journey_id_1, administration_1);
+    let Some(journey_id_1) = resolve_journey_id(
+        journeys_pk_type_converter,
+        journeys,
+        &journey_legacy_id_1,
+        stop_id,
+        None,
+    )?
+    else {
+        return Ok(None);
+    };
 
-    let _journey_id_2 = journeys_pk_type_converter
-        .get(&(journey_id_2, administration_2.clone()))
-        .ok_or("Unknown legacy ID")?;
+    let journey_legacy_id_2 = (journey_id_2, administration_2);
+    let Some(journey_id_2) = resolve_journey_id(
+        journeys_pk_type_converter,
+        journeys,
+        &journey_legacy_id_2,
+        stop_id,
+        None,
+    )?
+    else {
+        return Ok(None);
+    };
 
     // TODO: I haven't seen an is_guaranteed field in the doc. Check if this makes sense.
     // Note : There is two spaces in a row in the file, might not be useful but does not break anything
     // It is present in UMSTEIGL. Mabe a copy/paste leftover
     let is_guaranteed = is_guaranteed == "!";
 
-    Ok(ExchangeTimeJourney::new(
+    Ok(Some(ExchangeTimeJourney::new(
         auto_increment.next(),
         stop_id,
-        (journey_id_1, administration_1),
-        (journey_id_2, administration_2),
+        journey_id_1,
+        journey_legacy_id_1,
+        journey_id_2,
+        journey_legacy_id_2,
         duration,
         is_guaranteed,
         bit_field_id,
-    ))
+    )))
 }
 
 pub fn parse(
     path: &str,
-    journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    journeys_pk_type_converter: &JourneysPkTypeConverter,
+    journeys: &ResourceStorage<Journey>,
 ) -> Result<ResourceStorage<ExchangeTimeJourney>, Box<dyn Error>> {
-    ExchangeTimeJourneyParser::new().parse(path, journeys_pk_type_converter)
+    ExchangeTimeJourneyParser::new().parse(path, journeys_pk_type_converter, journeys)
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -169,6 +197,7 @@ pub fn parse(
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use crate::models::JourneyRouteEntry;
     use crate::parsing::tests::get_json_values;
     use pretty_assertions::assert_eq;
 
@@ -235,19 +264,22 @@ mod tests {
         };
 
         // The journeys_pk_type_converter is dummy and created just for testing purposes
-        let mut journeys_pk_type_converter: FxHashSet<JourneyId> = FxHashSet::default();
-        journeys_pk_type_converter.insert((23057, "000011".to_string()));
-        journeys_pk_type_converter.insert((1929, "000011".to_string()));
-        journeys_pk_type_converter.insert((1671, "000011".to_string()));
-        journeys_pk_type_converter.insert((24256, "000011".to_string()));
+        let mut journeys_pk_type_converter = JourneysPkTypeConverter::default();
+        journeys_pk_type_converter.insert((23057, "000011".to_string()), vec![1]);
+        journeys_pk_type_converter.insert((1929, "000011".to_string()), vec![2]);
+        journeys_pk_type_converter.insert((1671, "000011".to_string()), vec![3]);
+        journeys_pk_type_converter.insert((24256, "000011".to_string()), vec![4]);
+        let journeys = ResourceStorage::new(FxHashMap::default());
 
-        let data = row_converter(parser, &journeys_pk_type_converter).unwrap();
+        let data = row_converter(parser, &journeys_pk_type_converter, &journeys).unwrap();
         // First row
         let attribute = data.get(&1).unwrap();
         let reference = r#"
             {
                 "id":1,
                 "stop_id": 8501008,
+                "journey_id_1": 1,
+                "journey_id_2": 3,
                 "journey_legacy_id_1": 23057,
                 "administration_1": "000011",
                 "journey_legacy_id_2": 1671,
@@ -263,6 +295,8 @@ mod tests {
             {
                 "id":2,
                 "stop_id": 8501120,
+                "journey_id_1": 2,
+                "journey_id_2": 4,
                 "journey_legacy_id_1": 1929,
                 "administration_1": "000011",
                 "journey_legacy_id_2": 24256,
@@ -274,4 +308,44 @@ mod tests {
         let (attribute, reference) = get_json_values(attribute, reference).unwrap();
         assert_eq!(attribute, reference);
     }
+
+    #[test]
+    fn type_converter_region_v207() {
+        // Two journeys of the TU code 801 share the same number, but run in different regions.
+        let rows = vec![
+            "8572001 001234 000801 000777 000011 003         Aarau".to_string(),
+            "8578001 001234 000801 000777 000011 005         Stans".to_string(),
+        ];
+        let exchange_time_journey_parser = ExchangeTimeJourneyParser::new();
+        let parser = FileParser {
+            row_parser: exchange_time_journey_parser.row_parser.clone(),
+            rows,
+        };
+
+        let mut journey_1 = Journey::new(1, 1234, "000801".to_string());
+        journey_1.set_region("2".to_string());
+        journey_1.add_route_entry(JourneyRouteEntry::new(8572000, None, None));
+        journey_1.add_route_entry(JourneyRouteEntry::new(8572001, None, None));
+        let mut journey_2 = Journey::new(2, 1234, "000801".to_string());
+        journey_2.set_region("8".to_string());
+        journey_2.add_route_entry(JourneyRouteEntry::new(8578000, None, None));
+        journey_2.add_route_entry(JourneyRouteEntry::new(8578001, None, None));
+        let mut journey_3 = Journey::new(3, 777, "000011".to_string());
+        journey_3.add_route_entry(JourneyRouteEntry::new(8572001, None, None));
+        journey_3.add_route_entry(JourneyRouteEntry::new(8578001, None, None));
+        let journeys =
+            ResourceStorage::new(Journey::vec_to_map(vec![journey_1, journey_2, journey_3]));
+
+        let mut journeys_pk_type_converter = JourneysPkTypeConverter::default();
+        journeys_pk_type_converter.insert((1234, "000801".to_string()), vec![1, 2]);
+        journeys_pk_type_converter.insert((777, "000011".to_string()), vec![3]);
+
+        let data = row_converter(parser, &journeys_pk_type_converter, &journeys).unwrap();
+        let exchange_time = data.get(&1).unwrap();
+        assert_eq!(1, exchange_time.journey_id_1());
+        assert_eq!(3, exchange_time.journey_id_2());
+        let exchange_time = data.get(&2).unwrap();
+        assert_eq!(2, exchange_time.journey_id_1());
+        assert_eq!(3, exchange_time.journey_id_2());
+    }
 }
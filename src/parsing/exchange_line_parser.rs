@@ -457,4 +457,38 @@ mod tests {
         let (attribute, reference) = get_json_values(attribute, reference).unwrap();
         assert_eq!(attribute, reference);
     }
+
+    #[test]
+    fn specificity_v207() {
+        let rows = vec![
+            "8301113 000011 S   *        * 007000 B   *        * 003  Luino (I)".to_string(),
+            "1111135 sbg034 B   7339     H sbg034 TX  7341     H 000! Waldshut, Busbahnhof"
+                .to_string(),
+            "8509002 000011 RE  *        * 000065 S   12       * 008  Landquart".to_string(),
+        ];
+        let exchange_time_line_parser = ExchangeTimeLineParser::new();
+        let parser = FileParser {
+            row_parser: exchange_time_line_parser.row_parser.clone(),
+            rows,
+        };
+
+        let mut transport_types_pk_type_converter: FxHashMap<String, i32> = FxHashMap::default();
+        transport_types_pk_type_converter.insert("S".to_string(), 1);
+        transport_types_pk_type_converter.insert("B".to_string(), 2);
+        transport_types_pk_type_converter.insert("TX".to_string(), 3);
+        transport_types_pk_type_converter.insert("RE".to_string(), 4);
+
+        let data = row_converter(parser, &transport_types_pk_type_converter).unwrap();
+        let specificity = |id| {
+            let exchange_time = data.get(&id).unwrap();
+            (
+                exchange_time.line_1().specificity(),
+                exchange_time.line_2().specificity(),
+            )
+        };
+        // The wildcards (*) don't count.
+        assert_eq!((0, 0), specificity(1));
+        assert_eq!((2, 2), specificity(2));
+        assert_eq!((0, 1), specificity(3));
+    }
 }
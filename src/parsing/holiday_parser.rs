@@ -127,6 +127,7 @@ fn parse_name_translations(
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use crate::models::DayType;
     use crate::parsing::tests::get_json_values;
     use pretty_assertions::assert_eq;
 
@@ -197,4 +198,30 @@ mod tests {
         let (attribute, reference) = get_json_values(attribute, reference).unwrap();
         assert_eq!(attribute, reference);
     }
+
+    #[test]
+    fn day_type_v207() {
+        let rows =
+            vec!["25.12.2024 Weihnachtstag<deu>Noël<fra>Natale<ita>Christmas Day<eng>".to_string()];
+        let holiday_parser = HolidayParser::new();
+        let parser = FileParser {
+            row_parser: holiday_parser.row_parser.clone(),
+            rows,
+        };
+        let data = row_converter(parser).unwrap();
+        let holiday = data.get(&1).unwrap();
+        assert_eq!(Some("Christmas Day"), holiday.name(Language::English));
+        assert_eq!(Some("Weihnachtstag"), holiday.name(Language::German));
+
+        let is_holiday = |date: NaiveDate| date == holiday.date();
+        let day_type = |day: u32| {
+            let date = NaiveDate::from_ymd_opt(2024, 12, day).unwrap();
+            DayType::new(date, is_holiday(date))
+        };
+        // Wednesday 25 December 2024 is a holiday.
+        assert_eq!(DayType::Weekday, day_type(24));
+        assert_eq!(DayType::SundayOrHoliday, day_type(25));
+        assert_eq!(DayType::Saturday, day_type(28));
+        assert_eq!(DayType::SundayOrHoliday, day_type(29));
+    }
 }
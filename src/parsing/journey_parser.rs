@@ -10,6 +10,7 @@
 ///     - The journey number (primary key with the TU code)
 ///     - Transport company (TU) code (see File BETRIEB_*)
 ///         - For the TU code = 801, the region information must also be taken into account. This information is contained in line *I with the INFOTEXTCODE RN.
+///           It is stored in Journey::region and several journeys can therefore share the same (journey number, TU code) pair.
 ///     - Option
 ///         - NOT PART OF HRDF. 3-digit means of transport variant code without technical meaning
 ///     - (optional) Number of cycles
@@ -148,10 +149,39 @@
 /// ...
 /// `
 ///
-/// ## GR/SH-lines
+/// ## GR-lines
 ///
-/// - *GR lines: supported but not available in Switzerland.
-/// - *SH lines: supported but not available in Switzerland.
+/// - *GR lines: Border crossings (s. file GRENZHLT). Supported but not available in Switzerland. It includes:
+///     - Border point
+///     - Last stop before the border
+///     - First stop after the border
+///     - Departure time at the last stop before the border
+///     - Arrival time at the first stop after the border
+///
+/// ### Example (excerpt):
+///
+/// `
+/// *Z ...
+/// ...
+/// *GR 8500090 8500010 8014441 001215 001218 % Grenzpunkt 8500090 zwischen HS-Nr. 8500010 (Abfahrt 12:15) und HS-Nr. 8014441 (Ankunft 12:18)
+/// ...
+/// `
+///
+/// ## SH-lines
+///
+/// - *SH lines: Stop-specific validity (s. file BITFELD). Supported but not available in Switzerland. It includes:
+///     - Stop
+///     - Reference to the validity information. The stop is only served on these days.
+///     - (optional) Departure time
+///
+/// ### Example (excerpt):
+///
+/// `
+/// *Z ...
+/// ...
+/// *SH 8500010 001417 001215 % HS-Nr. 8500010 wird nur an den Gültigkeitstagen 001417 bedient (Abfahrt 12:15)
+/// ...
+/// `
 ///
 /// ## CI/CO lines
 ///
@@ -214,16 +244,19 @@ use nom::character::complete::{char, space1};
 use nom::combinator::{opt};
 use nom::Parser;
 use nom::sequence::preceded;
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::FxHashMap;
 
-use crate::{JourneyId, models::{Journey, JourneyMetadataEntry, JourneyMetadataType, JourneyRouteEntry, Model}, parsing::{
+use crate::{JourneyId, models::{InformationText, InformationTextCode, Journey, JourneyMetadataEntry, JourneyMetadataType, JourneyRouteEntry, Language, Model}, parsing::{
     ColumnDefinition, ExpectedType, FileParser, ParsedValue, RowDefinition,
     RowParser,
 }, storage::ResourceStorage, utils::{AutoIncrement, create_time_from_value}};
 
 use crate::parsing::ParserFnReturn;
 
-type JourneyAndTypeConverter = (ResourceStorage<Journey>, FxHashSet<JourneyId>);
+// (legacy_id, administration) => ids of the journeys.
+// For the TU code 801, the journey number is only unique within a region (*I RN), so several journeys can share the same key.
+pub type JourneysPkTypeConverter = FxHashMap<JourneyId, Vec<i32>>;
+type JourneyAndTypeConverter = (ResourceStorage<Journey>, JourneysPkTypeConverter);
 
 enum RowType {
     RowA = 1,
@@ -235,6 +268,8 @@ enum RowType {
     RowG = 7,
     RowH = 8,
     RowI = 9,
+    RowJ = 10,
+    RowK = 11,
 }
 
 pub struct JourneyParser {
@@ -352,6 +387,27 @@ impl JourneyParser {
         Ok((i2, vec![data.0, data.1.unwrap_or(""), data.2.unwrap_or("")]))
     }
 
+    fn get_parser_11(input: &str) -> ParserFnReturn {
+        let mut parser = (
+            preceded((tag("*GR"), space1), take(7usize)),
+            preceded(char(' '), opt(take(7usize))),
+            preceded(char(' '), opt(take(7usize))),
+            preceded(char(' '), opt(take(6usize))),
+            preceded(char(' '), opt(take(6usize)))
+        );
+        let (i2, data) = parser.parse(input)?;
+        Ok((i2, vec![data.0, data.1.unwrap_or(""), data.2.unwrap_or(""), data.3.unwrap_or(""), data.4.unwrap_or("")]))
+    }
+
+    fn get_parser_12(input: &str) -> ParserFnReturn {
+        let mut parser = (
+            preceded((tag("*SH"), space1), take(7usize)),
+            preceded(char(' '), take(6usize)),
+            opt(preceded(char(' '), take(6usize)))
+        );
+        let (i2, data) = parser.parse(input)?;
+        Ok((i2, vec![data.0, data.1, data.2.unwrap_or("")]))
+    }
 
     pub fn new() -> Self {
         Self {
@@ -452,6 +508,28 @@ impl JourneyParser {
                     ],
                     Self::get_parser_9
                 ));
+                // *GR (must be defined before the route rows, whose parser accepts any line)
+                rows.push(RowDefinition::new(
+                    RowType::RowJ as i32,
+                    vec![
+                        ColumnDefinition::new(ExpectedType::Integer32),
+                        ColumnDefinition::new(ExpectedType::OptionInteger32),
+                        ColumnDefinition::new(ExpectedType::OptionInteger32),
+                        ColumnDefinition::new(ExpectedType::OptionInteger32),
+                        ColumnDefinition::new(ExpectedType::OptionInteger32),
+                    ],
+                    Self::get_parser_11
+                ));
+                // *SH
+                rows.push(RowDefinition::new(
+                    RowType::RowK as i32,
+                    vec![
+                        ColumnDefinition::new(ExpectedType::Integer32),
+                        ColumnDefinition::new(ExpectedType::Integer32),
+                        ColumnDefinition::new(ExpectedType::OptionInteger32),
+                    ],
+                    Self::get_parser_12
+                ));
                 rows.push(RowDefinition::new(
                     RowType::RowI as i32,
                     vec![
@@ -472,6 +550,7 @@ impl JourneyParser {
         transport_types_pk_type_converter: &FxHashMap<String, i32>,
         attributes_pk_type_converter: &FxHashMap<String, i32>,
         directions_pk_type_converter: &FxHashMap<String, i32>,
+        information_texts: &ResourceStorage<InformationText>,
     ) -> Result<JourneyAndTypeConverter, Box<dyn Error>> {
         log::info!("Parsing {}...", self.file);
         let parser = FileParser::new(&format!("{}/{}", path, self.file), self.row_parser.clone())?;
@@ -480,6 +559,7 @@ impl JourneyParser {
             transport_types_pk_type_converter,
             attributes_pk_type_converter,
             directions_pk_type_converter,
+            information_texts,
         )?;
         Ok((ResourceStorage::new(data), pk_type_converter))
     }
@@ -490,10 +570,11 @@ fn row_converter(
     transport_types_pk_type_converter: &FxHashMap<String, i32>,
     attributes_pk_type_converter: &FxHashMap<String, i32>,
     directions_pk_type_converter: &FxHashMap<String, i32>,
-) -> Result<(FxHashMap<i32, Journey>, FxHashSet<JourneyId>), Box<dyn Error>> {
+    information_texts: &ResourceStorage<InformationText>,
+) -> Result<(FxHashMap<i32, Journey>, JourneysPkTypeConverter), Box<dyn Error>> {
     let auto_increment = AutoIncrement::new();
     let mut data = Vec::new();
-    let mut pk_type_converter = FxHashSet::default();
+    let mut pk_type_converter = FxHashMap::default();
 
     for x in parser.parse() {
         let (id, _, values) = x?;
@@ -513,7 +594,7 @@ fn row_converter(
             } else if id == RowType::RowD as i32 {
                 add_attribute(values, journey, attributes_pk_type_converter)?;
             } else if id == RowType::RowE as i32 {
-                add_information_text(values, journey);
+                add_information_text(values, journey, information_texts);
             } else if id == RowType::RowF as i32 {
                 set_line(values, journey)?;
             } else if id == RowType::RowG as i32 {
@@ -522,6 +603,10 @@ fn row_converter(
                 set_boarding_or_disembarking_exchange_time(values, journey);
             } else if id == RowType::RowI as i32 {
                 add_route_entry(values, journey);
+            } else if id == RowType::RowJ as i32 {
+                add_border_crossing(values, journey);
+            } else if id == RowType::RowK as i32 {
+                add_stop_bit_field(values, journey);
             } else {
                 unreachable!();
             }
@@ -536,11 +621,14 @@ fn row_converter(
 fn create_instance(
     values: Vec<ParsedValue>,
     auto_increment: &AutoIncrement,
-    pk_type_converter: &mut FxHashSet<JourneyId>,
+    pk_type_converter: &mut JourneysPkTypeConverter,
 ) -> Journey {
     let (legacy_id, administration) = row_a_from_parsed_values(values);
     let id = auto_increment.next();
-    pk_type_converter.insert((legacy_id, administration.to_owned()));
+    pk_type_converter
+        .entry((legacy_id, administration.to_owned()))
+        .or_default()
+        .push(id);
     Journey::new(id, legacy_id, administration)
 }
 
@@ -549,8 +637,66 @@ pub fn parse(
     transport_types_pk_type_converter: &FxHashMap<String, i32>,
     attributes_pk_type_converter: &FxHashMap<String, i32>,
     directions_pk_type_converter: &FxHashMap<String, i32>,
+    information_texts: &ResourceStorage<InformationText>,
 ) -> Result<JourneyAndTypeConverter, Box<dyn Error>> {
-    JourneyParser::new().parse(path, transport_types_pk_type_converter, attributes_pk_type_converter, directions_pk_type_converter)
+    JourneyParser::new().parse(path, transport_types_pk_type_converter, attributes_pk_type_converter, directions_pk_type_converter, information_texts)
+}
+
+/// Returns the id of the journey identified by (legacy_id, administration) which serves the stop, at the time if provided.
+/// The journey numbers of the TU code 801 are only unique within a region, which the files referencing journeys
+/// (GLEIS, UMSTEIGZ, ...) don't contain: the stop and the time are used to tell apart the journeys sharing the same number.
+/// Returns None, with a warning, if none or several of them match.
+pub fn resolve_journey_id(
+    journeys_pk_type_converter: &JourneysPkTypeConverter,
+    journeys: &ResourceStorage<Journey>,
+    journey_id: &JourneyId,
+    stop_id: i32,
+    time: Option<NaiveTime>,
+) -> Result<Option<i32>, Box<dyn Error>> {
+    let candidates = journeys_pk_type_converter
+        .get(journey_id)
+        .ok_or("Unknown legacy journey ID")?;
+
+    if let [id] = candidates[..] {
+        return Ok(Some(id));
+    }
+
+    let matches: Vec<_> = candidates
+        .iter()
+        .copied()
+        .filter(|&id| {
+            journeys
+                .find(id)
+                .is_some_and(|journey| serves_stop_at(journey, stop_id, time))
+        })
+        .collect();
+
+    match matches[..] {
+        [id] => Ok(Some(id)),
+        [] => {
+            log::warn!(
+                "No journey {journey_id:?} serves the stop {stop_id} at {time:?}, the row is skipped."
+            );
+            Ok(None)
+        }
+        _ => {
+            log::warn!(
+                "Several journeys {journey_id:?} serve the stop {stop_id} at {time:?}, the row is skipped."
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Returns true if the journey serves the stop, arriving or departing at the time if provided.
+fn serves_stop_at(journey: &Journey, stop_id: i32, time: Option<NaiveTime>) -> bool {
+    journey.route().iter().any(|route_entry| {
+        route_entry.stop_id() == stop_id
+            && time.is_none_or(|time| {
+                route_entry.arrival_time() == &Some(time)
+                    || route_entry.departure_time() == &Some(time)
+            })
+    })
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -693,7 +839,11 @@ fn row_e_from_parsed_values(
     )
 }
 
-fn add_information_text(values: Vec<ParsedValue>, journey: &mut Journey) {
+fn add_information_text(
+    values: Vec<ParsedValue>,
+    journey: &mut Journey,
+    information_texts: &ResourceStorage<InformationText>,
+) {
     let (
         code,
         from_stop_id,
@@ -706,6 +856,16 @@ fn add_information_text(values: Vec<ParsedValue>, journey: &mut Journey) {
     let arrival_time = create_time(arrival_time);
     let departure_time = create_time(departure_time);
 
+    if code == InformationTextCode::Region.to_string() {
+        // The region is only used by the TU code 801. It makes the journey number unique.
+        // If the information text is missing, its ID is kept as region so that journeys can still be told apart.
+        let region = information_texts
+            .find(information_text_id)
+            .and_then(|information_text| information_text.content(Language::German))
+            .map_or_else(|| information_text_id.to_string(), String::from);
+        journey.set_region(region);
+    }
+
     journey.add_metadata_entry(
         JourneyMetadataType::InformationText,
         JourneyMetadataEntry::new(
@@ -883,20 +1043,84 @@ fn row_i_from_parsed_values(mut values: Vec<ParsedValue>) -> (i32, Option<i32>,
 
 fn add_route_entry(values: Vec<ParsedValue>, journey: &mut Journey) {
     let (stop_id, arrival_time, departure_time) = row_i_from_parsed_values(values);
+    let alighting_allowed = arrival_time.is_none_or(|time| time >= 0);
+    let boarding_allowed = departure_time.is_none_or(|time| time >= 0);
     let arrival_time = create_time(arrival_time);
     let departure_time = create_time(departure_time);
 
-    journey.add_route_entry(JourneyRouteEntry::new(
-        stop_id,
-        arrival_time,
-        departure_time,
-    ));
+    let mut route_entry = JourneyRouteEntry::new(stop_id, arrival_time, departure_time);
+    route_entry.set_alighting_allowed(alighting_allowed);
+    route_entry.set_boarding_allowed(boarding_allowed);
+    journey.add_route_entry(route_entry);
 }
 
 // ------------------------------------------------------------------------------------------------
 // --- Helper Functions
 // ------------------------------------------------------------------------------------------------
 
+// RowJ parsing
+fn row_j_from_parsed_values(
+    mut values: Vec<ParsedValue>,
+) -> (i32, Option<i32>, Option<i32>, Option<i32>, Option<i32>) {
+    let border_point_id: i32 = values.remove(0).into();
+    let from_stop_id: Option<i32> = values.remove(0).into();
+    let until_stop_id: Option<i32> = values.remove(0).into();
+    let departure_time: Option<i32> = values.remove(0).into();
+    let arrival_time: Option<i32> = values.remove(0).into();
+    (
+        border_point_id,
+        from_stop_id,
+        until_stop_id,
+        departure_time,
+        arrival_time,
+    )
+}
+
+fn add_border_crossing(values: Vec<ParsedValue>, journey: &mut Journey) {
+    let (border_point_id, from_stop_id, until_stop_id, departure_time, arrival_time) =
+        row_j_from_parsed_values(values);
+
+    journey.add_metadata_entry(
+        JourneyMetadataType::BorderPoint,
+        JourneyMetadataEntry::new(
+            from_stop_id,
+            until_stop_id,
+            Some(border_point_id),
+            None,
+            create_time(departure_time),
+            create_time(arrival_time),
+            None,
+            None,
+        ),
+    );
+}
+
+// RowK parsing
+fn row_k_from_parsed_values(mut values: Vec<ParsedValue>) -> (i32, i32, Option<i32>) {
+    let stop_id: i32 = values.remove(0).into();
+    let bit_field_id: i32 = values.remove(0).into();
+    let departure_time: Option<i32> = values.remove(0).into();
+    (stop_id, bit_field_id, departure_time)
+}
+
+fn add_stop_bit_field(values: Vec<ParsedValue>, journey: &mut Journey) {
+    let (stop_id, bit_field_id, departure_time) = row_k_from_parsed_values(values);
+
+    journey.add_metadata_entry(
+        JourneyMetadataType::StopBitField,
+        JourneyMetadataEntry::new(
+            Some(stop_id),
+            Some(stop_id),
+            None,
+            Some(bit_field_id),
+            create_time(departure_time),
+            None,
+            None,
+            None,
+        ),
+    );
+}
+
 fn create_time(time: Option<i32>) -> Option<NaiveTime> {
     time.map(|value| {
         create_time_from_value(match value.abs() {
@@ -911,7 +1135,13 @@ mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     //use crate::parsing::tests::get_json_values;
+    use crate::{
+        models::{InformationTextCode, Language},
+        storage::{DataStorage, tests::data_storage_from},
+    };
+    use chrono::NaiveDate;
     use pretty_assertions::assert_eq;
+    use std::str::FromStr;
 
     #[test]
     fn parsing_rows_v207() {
@@ -1205,6 +1435,440 @@ mod tests {
         }
     }
 
+    #[test]
+    fn information_text_code_v207() {
+        let codes = ["hi", "JY", "RN", "ZN", "XY"];
+        let expected = [
+            InformationTextCode::Note,
+            InformationTextCode::SwissJourneyId,
+            InformationTextCode::Region,
+            InformationTextCode::TrainName,
+            InformationTextCode::Other("XY".to_string()),
+        ];
+
+        for (code, expected) in codes.iter().zip(expected) {
+            let information_text_code = InformationTextCode::from_str(code).unwrap();
+            assert_eq!(expected, information_text_code);
+            // Unknown codes must be kept losslessly.
+            assert_eq!(*code, &information_text_code.to_string());
+        }
+    }
+
+    /// - *I 1: whole journey, every day, in German and English
+    /// - *I 2: B to C, only on 03.03.2025, in German
+    /// - *I 3: only at D, every day, in German
+    /// - *I 4: train name, every day, in German
+    fn information_texts_fixture() -> DataStorage {
+        data_storage_from(&[
+            (
+                "BITFELD",
+                &[
+                    "000001 000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000",
+                ],
+            ),
+            (
+                "INFOTEXT_DE",
+                &[
+                    "000000001 Hinweis",
+                    "000000002 Bauarbeiten",
+                    "000000003 Halt auf Verlangen",
+                    "000000004 Glacier Express",
+                ],
+            ),
+            ("INFOTEXT_EN", &["000000001 Note"]),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G B   8500010 8500040                                     %",
+                    "*A VE 8500010 8500040 000000                               %",
+                    "*I hi                        000000001                     %",
+                    "*I hi 8500020 8500030 000001 000000002                     %",
+                    "*I hi 8500040 8500040        000000003                     %",
+                    "*I ZN                        000000004                     %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00810  00811                 %",
+                    "8500030 C                     00820  00821                 %",
+                    "8500040 D                     00830                        %",
+                ],
+            ),
+        ])
+    }
+
+    #[test]
+    fn information_texts_v207() {
+        let data_storage = information_texts_fixture();
+        let journey = data_storage.journeys().find(1).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+
+        assert_eq!(
+            vec![
+                (InformationTextCode::Note, "Hinweis"),
+                (InformationTextCode::Note, "Bauarbeiten"),
+                (InformationTextCode::Note, "Halt auf Verlangen"),
+                (InformationTextCode::TrainName, "Glacier Express"),
+            ],
+            journey.information_texts(&data_storage, date, Language::German)
+        );
+        // The *I 2 is not valid on the next day.
+        assert_eq!(
+            vec![
+                (InformationTextCode::Note, "Hinweis"),
+                (InformationTextCode::Note, "Halt auf Verlangen"),
+                (InformationTextCode::TrainName, "Glacier Express"),
+            ],
+            journey.information_texts(&data_storage, date.succ_opt().unwrap(), Language::German)
+        );
+        // The texts without content in the requested language are skipped.
+        assert_eq!(
+            vec![(InformationTextCode::Note, "Note")],
+            journey.information_texts(&data_storage, date, Language::English)
+        );
+    }
+
+    #[test]
+    fn information_texts_of_section_v207() {
+        let data_storage = information_texts_fixture();
+        let journey = data_storage.journeys().find(1).unwrap();
+        let date = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let texts = |departure_stop_id, arrival_stop_id| -> Vec<&str> {
+            journey
+                .information_texts_of_section(
+                    departure_stop_id,
+                    arrival_stop_id,
+                    &data_storage,
+                    date,
+                    Language::German,
+                )
+                .into_iter()
+                .map(|(_, content)| content)
+                .collect()
+        };
+
+        // A section only touching B does not overlap B to C.
+        assert_eq!(vec!["Hinweis", "Glacier Express"], texts(8500010, 8500020));
+        assert_eq!(
+            vec!["Hinweis", "Bauarbeiten", "Glacier Express"],
+            texts(8500020, 8500030)
+        );
+        // A text at a single stop applies if the section includes the stop.
+        assert_eq!(
+            vec!["Hinweis", "Halt auf Verlangen", "Glacier Express"],
+            texts(8500030, 8500040)
+        );
+        // The arrival stop must come after the departure stop.
+        assert!(texts(8500040, 8500010).is_empty());
+    }
+
+    #[test]
+    fn border_crossing_and_stop_bit_field_v207() {
+        let rows = vec![
+            "*Z 000003 000011                                           %".to_string(),
+            "*G ICE 8500090 8014441                                     %".to_string(),
+            "*GR 8500090 8500010 8014441 001215 001218                  %".to_string(),
+            "*SH 8500090 001417 001210                                  %".to_string(),
+            "8500010 Basel SBB                    01200                 %".to_string(),
+            "8500090 Basel Bad Bf          01208  01210                 %".to_string(),
+            "8014441 Weil am Rhein         01218                        %".to_string(),
+        ];
+        let parser = FileParser {
+            row_parser: JourneyParser::new().row_parser.clone(),
+            rows,
+        };
+        let transport_types_pk_type_converter = FxHashMap::from_iter([("ICE".to_string(), 1)]);
+        let (data, _) = row_converter(
+            parser,
+            &transport_types_pk_type_converter,
+            &FxHashMap::default(),
+            &FxHashMap::default(),
+            &ResourceStorage::new(FxHashMap::default()),
+        )
+        .unwrap();
+        let journey = &data[&1];
+
+        let [border_crossing] = journey.border_crossings() else {
+            panic!("Expected exactly one border crossing.");
+        };
+        assert_eq!(Some(8500090), border_crossing.resource_id());
+        assert_eq!(Some(8500010), border_crossing.from_stop_id());
+        assert_eq!(Some(8014441), border_crossing.until_stop_id());
+        assert_eq!(
+            NaiveTime::from_hms_opt(12, 15, 0),
+            border_crossing.departure_time()
+        );
+        assert_eq!(
+            NaiveTime::from_hms_opt(12, 18, 0),
+            border_crossing.arrival_time()
+        );
+
+        let [stop_bit_field] = journey.stop_bit_fields() else {
+            panic!("Expected exactly one stop bit field.");
+        };
+        assert_eq!(Some(8500090), stop_bit_field.from_stop_id());
+        assert_eq!(Some(1417), stop_bit_field.bit_field_id());
+        assert_eq!(
+            NaiveTime::from_hms_opt(12, 10, 0),
+            stop_bit_field.departure_time()
+        );
+    }
+
+    #[test]
+    fn boarding_and_alighting_v207() {
+        let rows = vec![
+            "*Z 000003 000011                                           %".to_string(),
+            "*G ICE 8500010 8503000                                     %".to_string(),
+            "8500010 Basel SBB                    01200                 %".to_string(),
+            "8500218 Olten                -01230 -01232                 %".to_string(),
+            "8503000 Zürich HB             01310                        %".to_string(),
+        ];
+        let parser = FileParser {
+            row_parser: JourneyParser::new().row_parser.clone(),
+            rows,
+        };
+        let transport_types_pk_type_converter = FxHashMap::from_iter([("ICE".to_string(), 1)]);
+        let (data, _) = row_converter(
+            parser,
+            &transport_types_pk_type_converter,
+            &FxHashMap::default(),
+            &FxHashMap::default(),
+            &ResourceStorage::new(FxHashMap::default()),
+        )
+        .unwrap();
+        let route = data[&1].route();
+
+        // Negative times: neither boarding nor alighting at Olten.
+        assert!(route[0].boarding_allowed());
+        assert!(!route[1].alighting_allowed());
+        assert!(!route[1].boarding_allowed());
+        assert_eq!(&NaiveTime::from_hms_opt(12, 30, 0), route[1].arrival_time());
+        assert!(route[2].alighting_allowed());
+    }
+
+    #[test]
+    fn zoned_times_daylight_saving_time_v207() {
+        let rows = vec![
+            "*Z 000003 000011                                           %".to_string(),
+            "*G ICE 8500010 8503000                                     %".to_string(),
+            "8500010 Basel SBB                    00150                 %".to_string(),
+            "8500218 Olten                 00230  00232                 %".to_string(),
+            "8503000 Zürich HB             00310                        %".to_string(),
+        ];
+        let parser = FileParser {
+            row_parser: JourneyParser::new().row_parser.clone(),
+            rows,
+        };
+        let transport_types_pk_type_converter = FxHashMap::from_iter([("ICE".to_string(), 1)]);
+        let (data, _) = row_converter(
+            parser,
+            &transport_types_pk_type_converter,
+            &FxHashMap::default(),
+            &FxHashMap::default(),
+            &ResourceStorage::new(FxHashMap::default()),
+        )
+        .unwrap();
+        let journey = &data[&1];
+        let timezone = crate::models::DEFAULT_TIMEZONE;
+
+        // Regular day.
+        let date = NaiveDate::from_ymd_opt(2025, 3, 29).unwrap();
+        assert_eq!(
+            80,
+            journey.elapsed_minutes(8500010, 8503000, date, timezone)
+        );
+
+        // Start of daylight saving time: 02:00 becomes 03:00, the times in the gap are moved to 03:00.
+        let date = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap();
+        assert_eq!(
+            20,
+            journey.elapsed_minutes(8500010, 8503000, date, timezone)
+        );
+        assert_eq!(
+            10,
+            journey.elapsed_minutes(8500218, 8503000, date, timezone)
+        );
+        assert_eq!(
+            "2025-03-30T03:00:00+02:00",
+            journey
+                .arrival_at_of_zoned(8500218, date, timezone)
+                .to_rfc3339()
+        );
+        assert_eq!(
+            "2025-03-30T03:00:00+02:00",
+            journey
+                .departure_at_of_zoned(8500218, date, timezone)
+                .to_rfc3339()
+        );
+        assert_eq!(
+            "2025-03-30T03:10:00+02:00",
+            journey
+                .arrival_at_of_zoned(8503000, date, timezone)
+                .to_rfc3339()
+        );
+
+        // The date-times never go backwards along the route.
+        let route_at = journey.route_at_zoned(date, timezone);
+        let date_times: Vec<_> = route_at
+            .iter()
+            .flat_map(|&(arrival, departure)| [arrival, departure])
+            .flatten()
+            .collect();
+        assert!(date_times.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        // End of daylight saving time: 03:00 becomes 02:00, the hour from 02:00 to 03:00 is repeated.
+        let date = NaiveDate::from_ymd_opt(2025, 10, 26).unwrap();
+        assert_eq!(
+            140,
+            journey.elapsed_minutes(8500010, 8503000, date, timezone)
+        );
+        assert_eq!(
+            "2025-10-26T02:30:00+02:00",
+            journey
+                .arrival_at_of_zoned(8500218, date, timezone)
+                .to_rfc3339()
+        );
+        assert_eq!(
+            "2025-10-26T03:10:00+01:00",
+            journey
+                .arrival_at_of_zoned(8503000, date, timezone)
+                .to_rfc3339()
+        );
+    }
+
+    #[test]
+    fn region_disambiguates_journeys_v207() {
+        // Two journeys of the TU code 801 sharing the same number, but in different regions.
+        let rows = vec![
+            "*Z 001234 000801                                           %".to_string(),
+            "*G B   8500090 8503000                                     %".to_string(),
+            "*I RN                        000000001                     %".to_string(),
+            "8500090 Basel Bad Bf                 00740                 %".to_string(),
+            "8503000 Zürich HB             00900                        %".to_string(),
+            "*Z 001234 000801                                           %".to_string(),
+            "*G B   8507000 8508500                                     %".to_string(),
+            "*I RN                        000000002                     %".to_string(),
+            "8507000 Bern                         00810                 %".to_string(),
+            "8508500 Thun                  00840                        %".to_string(),
+        ];
+        let parser = FileParser {
+            row_parser: JourneyParser::new().row_parser.clone(),
+            rows,
+        };
+        let transport_types_pk_type_converter = FxHashMap::from_iter([("B".to_string(), 1)]);
+        let mut region_2 = InformationText::new(1);
+        region_2.set_content(Language::German, "2");
+        let mut region_8 = InformationText::new(2);
+        region_8.set_content(Language::German, "8");
+        let information_texts =
+            ResourceStorage::new(InformationText::vec_to_map(vec![region_2, region_8]));
+
+        let (data, pk_type_converter) = row_converter(
+            parser,
+            &transport_types_pk_type_converter,
+            &FxHashMap::default(),
+            &FxHashMap::default(),
+            &information_texts,
+        )
+        .unwrap();
+
+        let journey_id = (1234, "000801".to_string());
+        assert_eq!(vec![1, 2], pk_type_converter[&journey_id]);
+        assert_eq!(Some("2"), data[&1].region());
+        assert_eq!(Some("8"), data[&2].region());
+
+        let journeys = ResourceStorage::new(data);
+        let resolve = |stop_id| {
+            resolve_journey_id(&pk_type_converter, &journeys, &journey_id, stop_id, None).unwrap()
+        };
+        assert_eq!(Some(1), resolve(8500090));
+        assert_eq!(Some(2), resolve(8508500));
+        // No journey 1234 serves the stop.
+        assert_eq!(None, resolve(8500010));
+    }
+
+    /// - 1234 (801, region 1): A 08:00, H 08:30
+    /// - 1234 (801, region 2): H 09:00, B 09:30
+    /// - 5: A 07:50, H 08:20
+    /// - 6: H 08:35, D 09:00
+    #[test]
+    fn colliding_journeys_serving_the_same_stop_v207() {
+        let data_storage = data_storage_from(&[
+            (
+                "FPLAN",
+                &[
+                    "*Z 001234 000801                                           %",
+                    "*G B   8500010 8500080                                     %",
+                    "*A VE 8500010 8500080 000000                               %",
+                    "*I RN                        000000001                     %",
+                    "8500010 A                            00800                 %",
+                    "8500080 H                     00830                        %",
+                    "*Z 001234 000801                                           %",
+                    "*G B   8500080 8500020                                     %",
+                    "*A VE 8500080 8500020 000000                               %",
+                    "*I RN                        000000002                     %",
+                    "8500080 H                            00900                 %",
+                    "8500020 B                     00930                        %",
+                    "*Z 000005 000011                                           %",
+                    "*G B   8500010 8500080                                     %",
+                    "*A VE 8500010 8500080 000000                               %",
+                    "8500010 A                            00750                 %",
+                    "8500080 H                     00820                        %",
+                    "*Z 000006 000011                                           %",
+                    "*G B   8500080 8500040                                     %",
+                    "*A VE 8500080 8500040 000000                               %",
+                    "8500080 H                            00835                 %",
+                    "8500040 D                     00900                        %",
+                ],
+            ),
+            (
+                "GLEISE_LV95",
+                &[
+                    "8500080 001234 000801 #0000001 0830       ",
+                    "8500080 001234 000801 #0000002 0900       ",
+                    "8500080 001234 000801 #0000001            ",
+                    "8500030 001234 000801 #0000001            ",
+                    "8500080 #0000001 G '1'",
+                    "8500080 #0000002 G '2'",
+                    "8500030 #0000001 G '3'",
+                ],
+            ),
+            (
+                "UMSTEIGZ",
+                &[
+                    "8500010 000005 000011 001234 000801 003         A",
+                    "8500080 001234 000801 000006 000011 004         H",
+                ],
+            ),
+        ]);
+
+        // Both journeys 1234 serve H, the time tells them apart. Without time, or at a stop neither of them serves,
+        // the row is skipped.
+        let mut journey_platforms: Vec<_> = data_storage
+            .journey_platform()
+            .entries()
+            .into_iter()
+            .map(|journey_platform| (journey_platform.journey_id(), journey_platform.time()))
+            .collect();
+        journey_platforms.sort();
+        assert_eq!(
+            vec![
+                (1, NaiveTime::from_hms_opt(8, 30, 0)),
+                (2, NaiveTime::from_hms_opt(9, 0, 0)),
+            ],
+            journey_platforms
+        );
+
+        // Only the 1234 of the region 1 serves A, the exchange time at H is ambiguous.
+        let exchange_times = data_storage.exchange_times_journey().entries();
+        assert_eq!(1, exchange_times.len());
+        assert_eq!(
+            (3, 1),
+            (
+                exchange_times[0].journey_id_1(),
+                exchange_times[0].journey_id_2()
+            )
+        );
+    }
+
     // #[test]
     // fn type_converter_row_a_v207() {
     //     let rows = vec![
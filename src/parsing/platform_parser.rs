@@ -9,12 +9,12 @@ use nom::character::complete::{char, space1};
 use nom::combinator::{opt, recognize, rest};
 use nom::Parser;
 use nom::sequence::preceded;
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::FxHashMap;
 
-use crate::{JourneyId, Version, models::{CoordinateSystem, Coordinates, JourneyPlatform, Model, Platform}, parsing::{
+use crate::{Version, models::{CoordinateSystem, Coordinates, Journey, JourneyPlatform, Model, Platform}, parsing::{
     ColumnDefinition, ExpectedType, FileParser, ParsedValue, RowDefinition,
     RowParser,
-}, storage::ResourceStorage, utils::{AutoIncrement, create_time_from_value}};
+}, parsing::journey_parser::{JourneysPkTypeConverter, resolve_journey_id}, storage::ResourceStorage, utils::{AutoIncrement, create_time_from_value}};
 
 use crate::parsing::ParserFnReturn;
 
@@ -202,7 +202,8 @@ impl PlatformParser {
         &self,
         version: Version,
         path: &str,
-        journeys_pk_type_converter: &FxHashSet<JourneyId>,
+        journeys_pk_type_converter: &JourneysPkTypeConverter,
+        journeys: &ResourceStorage<Journey>,
     ) -> Result<(ResourceStorage<JourneyPlatform>, ResourceStorage<Platform>), Box<dyn Error>> {
         log::info!("Parsing {}...", self.files[0]);
         let auto_increment = AutoIncrement::new();
@@ -264,14 +265,18 @@ impl PlatformParser {
 
         let mut platforms = Platform::vec_to_map(platforms);
 
+        let journey_platform_auto_increment = AutoIncrement::new();
         let journey_platform = journey_platform
             .into_iter()
-            .map(|values| {
+            .filter_map(|values| {
                 create_journey_instance(
                     values,
+                    &journey_platform_auto_increment,
                     journeys_pk_type_converter,
+                    journeys,
                     &platforms_pk_type_converter,
                 )
+                .transpose()
             })
             .collect::<Result<Vec<_>, _>>()?;
         let journey_platform = JourneyPlatform::vec_to_map(journey_platform);
@@ -384,9 +389,11 @@ fn create_instance(
 
 fn create_journey_instance(
     mut values: Vec<ParsedValue>,
-    journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    auto_increment: &AutoIncrement,
+    journeys_pk_type_converter: &JourneysPkTypeConverter,
+    journeys: &ResourceStorage<Journey>,
     platforms_pk_type_converter: &FxHashMap<(i32, i32), i32>,
-) -> Result<JourneyPlatform, Box<dyn Error>> {
+) -> Result<Option<JourneyPlatform>, Box<dyn Error>> {
     let stop_id: i32 = values.remove(0).into();
     let journey_id: i32 = values.remove(0).into();
     let administration: String = values.remove(0).into();
@@ -394,31 +401,42 @@ fn create_journey_instance(
     let time: Option<i32> = values.remove(0).into();
     let bit_field_id: Option<i32> = values.remove(0).into();
 
-    let _journey_id = journeys_pk_type_converter
-        .get(&(journey_id, administration.clone()))
-        .ok_or("Unknown legacy journey ID")?;
+    let time = time.map(|x| create_time_from_value(x as u32));
+
+    let journey_legacy_id = journey_id;
+    let Some(journey_id) = resolve_journey_id(
+        journeys_pk_type_converter,
+        journeys,
+        &(journey_legacy_id, administration.clone()),
+        stop_id,
+        time,
+    )?
+    else {
+        return Ok(None);
+    };
 
     let platform_id = *platforms_pk_type_converter
         .get(&(stop_id, index))
         .ok_or("Unknown legacy platform ID")?;
 
-    let time = time.map(|x| create_time_from_value(x as u32));
-
-    Ok(JourneyPlatform::new(
+    Ok(Some(JourneyPlatform::new(
+        auto_increment.next(),
         journey_id,
+        journey_legacy_id,
         administration,
         platform_id,
         time,
         bit_field_id,
-    ))
+    )))
 }
 
 pub fn parse(
     version: Version,
     path: &str,
-    journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    journeys_pk_type_converter: &JourneysPkTypeConverter,
+    journeys: &ResourceStorage<Journey>,
 ) -> Result<(ResourceStorage<JourneyPlatform>, ResourceStorage<Platform>), Box<dyn Error>> {
-    PlatformParser::new(version).parse(version, path, journeys_pk_type_converter)
+    PlatformParser::new(version).parse(version, path, journeys_pk_type_converter, journeys)
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -505,3 +523,182 @@ fn parse_platform_data(
 
     Ok((code, sectors))
 }
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::storage::{DataStorage, tests::data_storage_from};
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    /// - IC 1 (000011): A 08:00, B 08:30, platform 1 at A.
+    /// - IC 1 (000022): A 09:00, B 09:30, platform 2 at A.
+    /// - IC 2: A 08:00, B 08:30, platform 1 at A, platform 2 at 08:00 and platform 3 at 09:00.
+    /// - IC 3: A 08:00, B 08:30, platform 1 at A and platform 3 on 03.03.2025,
+    ///   platform 4 at B on 03.03.2025 and platform 5 at 08:30.
+    fn platform_fixture() -> DataStorage {
+        data_storage_from(&[
+            ("BAHNHOF", &["8500010     A$<1>", "8500020     B$<1>"]),
+            (
+                "BITFELD",
+                &[
+                    "000001 000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000",
+                ],
+            ),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G IC  8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00830                        %",
+                    "*Z 000001 000022                                           %",
+                    "*G IC  8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "8500010 A                            00900                 %",
+                    "8500020 B                     00930                        %",
+                    "*Z 000002 000011                                           %",
+                    "*G IC  8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00830                        %",
+                    "*Z 000003 000011                                           %",
+                    "*G IC  8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00830                        %",
+                ],
+            ),
+            (
+                "GLEISE_LV95",
+                &[
+                    "8500010 000001 000011 #0000001            ",
+                    "8500010 000001 000022 #0000002            ",
+                    "8500010 000002 000011 #0000001            ",
+                    "8500010 000002 000011 #0000002 0800       ",
+                    "8500010 000002 000011 #0000003 0900       ",
+                    "8500010 000003 000011 #0000001            ",
+                    "8500010 000003 000011 #0000003      000001",
+                    "8500020 000003 000011 #0000001      000001",
+                    "8500020 000003 000011 #0000002 0830       ",
+                    "8500010 #0000001 G '1'",
+                    "8500010 #0000002 G '2'",
+                    "8500010 #0000003 G '3'",
+                    "8500020 #0000001 G '4'",
+                    "8500020 #0000002 G '5' A 'CD'",
+                ],
+            ),
+        ])
+    }
+
+    fn platform_name(
+        data_storage: &DataStorage,
+        journey_id: i32,
+        route_index: usize,
+        day: u32,
+    ) -> Option<&str> {
+        data_storage
+            .journeys()
+            .find(journey_id)
+            .unwrap()
+            .platform_at(
+                route_index,
+                NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+                data_storage,
+            )
+            .map(|platform| platform.name())
+    }
+
+    #[test]
+    fn row_parser_v207() {
+        let rows = vec![
+            "8500010 000002 000011 #0000002 0800       ".to_string(),
+            "8500010 000003 000011 #0000003      000001".to_string(),
+            "8500020 #0000002 G '5' A 'CD'".to_string(),
+        ];
+        let platform_parser = PlatformParser::new(Version::V_5_40_41_2_0_7);
+        let parser = FileParser {
+            row_parser: platform_parser.row_parser.clone(),
+            rows,
+        };
+        let mut parser_iterator = parser.parse();
+        // First row
+        let (id, _, mut parsed_values) = parser_iterator.next().unwrap().unwrap();
+        assert_eq!(RowType::RowJourneyPlatform as i32, id);
+        let stop_id: i32 = parsed_values.remove(0).into();
+        assert_eq!(8500010, stop_id);
+        let journey_id: i32 = parsed_values.remove(0).into();
+        assert_eq!(2, journey_id);
+        let administration: String = parsed_values.remove(0).into();
+        assert_eq!("000011", &administration);
+        let index: i32 = parsed_values.remove(0).into();
+        assert_eq!(2, index);
+        let time: Option<i32> = parsed_values.remove(0).into();
+        assert_eq!(Some(800), time);
+        let bit_field_id: Option<i32> = parsed_values.remove(0).into();
+        assert_eq!(None, bit_field_id);
+        // Second row
+        let (_, _, mut parsed_values) = parser_iterator.next().unwrap().unwrap();
+        let time: Option<i32> = parsed_values.remove(4).into();
+        assert_eq!(None, time);
+        let bit_field_id: Option<i32> = parsed_values.remove(4).into();
+        assert_eq!(Some(1), bit_field_id);
+        // Third row
+        let (id, _, mut parsed_values) = parser_iterator.next().unwrap().unwrap();
+        assert_eq!(RowType::RowPlatform as i32, id);
+        let stop_id: i32 = parsed_values.remove(0).into();
+        assert_eq!(8500020, stop_id);
+        let index: i32 = parsed_values.remove(0).into();
+        assert_eq!(2, index);
+        let platform_data: String = parsed_values.remove(0).into();
+        assert_eq!(
+            (String::from("5"), Some(String::from("CD"))),
+            parse_platform_data(platform_data).unwrap()
+        );
+    }
+
+    #[test]
+    fn type_converter_v207() {
+        let data_storage = platform_fixture();
+
+        // The journey number 1 exists for two administrations, each one has its own platform.
+        for (journey_id, administration, name) in [(1, "000011", "1"), (2, "000022", "2")] {
+            let journey = data_storage.journeys().find(journey_id).unwrap();
+            assert_eq!(
+                (1, administration),
+                (journey.legacy_id(), journey.administration())
+            );
+            let journey_platform_ids = &data_storage.journey_platforms_by_journey_id()[&journey_id];
+            assert_eq!(1, journey_platform_ids.len());
+            let journey_platform = data_storage
+                .journey_platform()
+                .find(journey_platform_ids[0])
+                .unwrap();
+            assert_eq!(administration, journey_platform.administration());
+            assert_eq!(Some(name), platform_name(&data_storage, journey_id, 0, 4));
+        }
+    }
+
+    #[test]
+    fn platform_at_time_v207() {
+        let data_storage = platform_fixture();
+
+        // The assignment at 08:00 takes precedence, the one at 09:00 does not apply.
+        assert_eq!(Some("2"), platform_name(&data_storage, 3, 0, 4));
+        assert_eq!(None, platform_name(&data_storage, 3, 1, 4));
+    }
+
+    #[test]
+    fn platform_at_bit_field_v207() {
+        let data_storage = platform_fixture();
+
+        // The assignment of the bit field takes precedence on its days only.
+        assert_eq!(Some("3"), platform_name(&data_storage, 4, 0, 3));
+        assert_eq!(Some("1"), platform_name(&data_storage, 4, 0, 4));
+        // The assignment at a time takes precedence over the one of a bit field.
+        assert_eq!(Some("5"), platform_name(&data_storage, 4, 1, 3));
+        assert_eq!(Some("5"), platform_name(&data_storage, 4, 1, 4));
+    }
+}
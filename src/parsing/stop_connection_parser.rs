@@ -4,16 +4,21 @@
 use std::error::Error;
 use nom::bytes::complete::{tag, take};
 use nom::character::complete::space1;
+use nom::combinator::rest;
 use nom::Parser;
 use nom::sequence::preceded;
 use rustc_hash::FxHashMap;
 
-use crate::{models::{Model, StopConnection}, parsing::{
+use crate::{models::{Model, StopConnection, StopGroup}, parsing::{
     ColumnDefinition, ExpectedType, FileParser,
     ParsedValue, RowDefinition, RowParser,
 }, storage::ResourceStorage, utils::AutoIncrement};
 use crate::parsing::ParserFnReturn;
 
+type StopConnectionsAndStopGroups = (ResourceStorage<StopConnection>, ResourceStorage<StopGroup>);
+type FxHashMapsOfStopConnectionsAndStopGroups =
+    (FxHashMap<i32, StopConnection>, FxHashMap<i32, StopGroup>);
+
 enum RowType {
     RowA = 1,
     RowB = 2,
@@ -57,10 +62,11 @@ impl StopConnectionParser {
     fn get_parser_3(input: &str) -> ParserFnReturn {
         let mut parser = (
             take(7usize),
-            tag(":")
+            tag(":"),
+            rest
         );
-        let (i2, _) = parser.parse(input)?;
-        Ok((i2, vec![]))
+        let (i2, data) = parser.parse(input)?;
+        Ok((i2, vec![data.0, data.2]))
     }
 
     pub fn new() -> Self {
@@ -85,10 +91,13 @@ impl StopConnectionParser {
                     ],
                     Self::get_parser_2
                 ));
-                // This row is ignored.
+                // This row contains a meta stop and the stops it groups.
                 rows.push(RowDefinition::new(
                     RowType::RowC as i32,
-                    vec![],
+                    vec![
+                        ColumnDefinition::new(ExpectedType::Integer32),
+                        ColumnDefinition::new(ExpectedType::String),
+                    ],
                     Self::get_parser_3,
                 ));
                 rows
@@ -100,20 +109,24 @@ impl StopConnectionParser {
         &self,
         path: &str,
         attributes_pk_type_converter: &FxHashMap<String, i32>,
-    ) -> Result<ResourceStorage<StopConnection>, Box<dyn Error>> {
+    ) -> Result<StopConnectionsAndStopGroups, Box<dyn Error>> {
         log::info!("Parsing {}...", self.file);
         let parser = FileParser::new(&format!("{}/{}", path, self.file), self.row_parser.clone())?;
-        let data = row_converter(parser, attributes_pk_type_converter)?;
-        Ok(ResourceStorage::new(data))
+        let (data, stop_groups) = row_converter(parser, attributes_pk_type_converter)?;
+        Ok((
+            ResourceStorage::new(data),
+            ResourceStorage::new(stop_groups),
+        ))
     }
 }
 
 fn row_converter(
     parser: FileParser,
     attributes_pk_type_converter: &FxHashMap<String, i32>,
-) -> Result<FxHashMap<i32, StopConnection>, Box<dyn Error>>  {
+) -> Result<FxHashMapsOfStopConnectionsAndStopGroups, Box<dyn Error>> {
     let auto_increment = AutoIncrement::new();
     let mut data = Vec::new();
+    let mut stop_groups: FxHashMap<i32, StopGroup> = FxHashMap::default();
     for x in parser.parse() {
         let (id, _, values) = x?;
         match id.try_into() {
@@ -122,11 +135,11 @@ fn row_converter(
                     data.push(create_instance(values, &auto_increment));
                 }
             }
+            Ok(RowType::RowC) => add_stop_group(values, &mut stop_groups)?,
             _ => {
                 let stop_connection = data.last_mut().ok_or("Type A row missing.")?;
                 match id.try_into() {
                     Ok(RowType::RowB) => set_attribute(values, stop_connection, attributes_pk_type_converter)?,
-                    Ok(RowType::RowC) => {},
                     _ => unreachable!()
                 }
             }
@@ -134,7 +147,7 @@ fn row_converter(
         }
     }
     let data = StopConnection::vec_to_map(data);
-    Ok(data)
+    Ok((data, stop_groups))
 }
 
 fn create_instance(mut values: Vec<ParsedValue>, auto_increment: &AutoIncrement) -> StopConnection {
@@ -147,7 +160,7 @@ fn create_instance(mut values: Vec<ParsedValue>, auto_increment: &AutoIncrement)
 pub fn parse(
     path: &str,
     attributes_pk_type_converter: &FxHashMap<String, i32>,
-) -> Result<ResourceStorage<StopConnection>, Box<dyn Error>> {
+) -> Result<StopConnectionsAndStopGroups, Box<dyn Error>> {
     StopConnectionParser::new().parse(path, attributes_pk_type_converter)
 }
 
@@ -167,3 +180,53 @@ fn set_attribute(
     current_instance.set_attribute(attribute_id);
     Ok(())
 }
+
+fn add_stop_group(
+    mut values: Vec<ParsedValue>,
+    stop_groups: &mut FxHashMap<i32, StopGroup>,
+) -> Result<(), Box<dyn Error>> {
+    let id: i32 = values.remove(0).into();
+    let stop_ids: String = values.remove(0).into();
+    let stop_ids = stop_ids
+        .split_whitespace()
+        .map(|stop_id| stop_id.parse())
+        .collect::<Result<Vec<i32>, _>>()?;
+    // A meta stop can be spread over several rows.
+    stop_groups
+        .entry(id)
+        .or_insert_with(|| StopGroup::new(id, Vec::new()))
+        .add_stop_ids(stop_ids);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::parsing::tests::get_json_values;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn type_converter_v207() {
+        let rows = vec![
+            "8500010 8500013 005".to_string(),
+            "8500010: 8500013 8500016".to_string(),
+            "8500010: 8500090".to_string(),
+        ];
+        let stop_connection_parser = StopConnectionParser::new();
+        let parser = FileParser {
+            row_parser: stop_connection_parser.row_parser.clone(),
+            rows,
+        };
+        let (data, stop_groups) = row_converter(parser, &FxHashMap::default()).unwrap();
+        assert_eq!(1, data.len());
+        let stop_group = stop_groups.get(&8500010).unwrap();
+        let reference = r#"
+            {
+                "id": 8500010,
+                "stop_ids": [8500013, 8500016, 8500090]
+            }"#;
+        let (stop_group, reference) = get_json_values(stop_group, reference).unwrap();
+        assert_eq!(stop_group, reference);
+    }
+}
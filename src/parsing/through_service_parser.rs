@@ -6,9 +6,9 @@ use nom::bytes::complete::take;
 use nom::character::complete::space1;
 use nom::Parser;
 use nom::sequence::preceded;
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::FxHashMap;
 
-use crate::{JourneyId, models::{Model, ThroughService}, parsing::{ColumnDefinition, ExpectedType, FileParser, ParsedValue, RowDefinition, RowParser}, storage::ResourceStorage, utils::AutoIncrement};
+use crate::{models::{Model, ThroughService}, parsing::{ColumnDefinition, ExpectedType, FileParser, ParsedValue, RowDefinition, RowParser}, parsing::journey_parser::JourneysPkTypeConverter, storage::ResourceStorage, utils::AutoIncrement};
 use crate::parsing::ParserFnReturn;
 
 pub struct ThroughServiceParser {
@@ -58,7 +58,7 @@ impl ThroughServiceParser {
     fn parse(
         &self,
         path: &str,
-        journeys_pk_type_converter: &FxHashSet<JourneyId>,
+        journeys_pk_type_converter: &JourneysPkTypeConverter,
     ) -> Result<ResourceStorage<ThroughService>, Box<dyn Error>> {
         log::info!("Parsing {}...", self.file);
         let parser = FileParser::new(&format!("{}/{}", path, self.file), self.row_parser.clone())?;
@@ -67,7 +67,7 @@ impl ThroughServiceParser {
     }
 }
 
-fn row_converter(parser: FileParser, journeys_pk_type_converter: &FxHashSet<JourneyId>) -> Result<FxHashMap<i32, ThroughService>, Box<dyn Error>>{
+fn row_converter(parser: FileParser, journeys_pk_type_converter: &JourneysPkTypeConverter) -> Result<FxHashMap<i32, ThroughService>, Box<dyn Error>>{
     let auto_increment = AutoIncrement::new();
 
     let data = parser
@@ -85,7 +85,7 @@ fn row_converter(parser: FileParser, journeys_pk_type_converter: &FxHashSet<Jour
 fn create_instance(
     mut values: Vec<ParsedValue>,
     auto_increment: &AutoIncrement,
-    journeys_pk_type_converter: &FxHashSet<JourneyId>,
+    journeys_pk_type_converter: &JourneysPkTypeConverter,
 ) -> Result<ThroughService, Box<dyn Error>> {
     let journey_1_id: i32 = values.remove(0).into();
     let journey_1_administration: String = values.remove(0).into();
@@ -117,7 +117,7 @@ fn create_instance(
     ))
 }
 
-pub fn parse(path: &str, journeys_pk_type_converter: &FxHashSet<JourneyId>) -> Result<ResourceStorage<ThroughService>, Box<dyn Error>> {
+pub fn parse(path: &str, journeys_pk_type_converter: &JourneysPkTypeConverter) -> Result<ResourceStorage<ThroughService>, Box<dyn Error>> {
     ThroughServiceParser::new().parse(path, journeys_pk_type_converter)
 }
 
@@ -111,16 +111,18 @@ fn create_instance(
 
     let start_date = NaiveDate::parse_from_str(&start_date, "%d.%m.%Y")?;
     let end_date = NaiveDate::parse_from_str(&end_date, "%d.%m.%Y")?;
-    let other_data: Vec<String> = other_data.split('$').map(String::from).collect();
+    let mut other_data = other_data.split('$').map(String::from);
 
-    let rows = vec![
+    let mut rows = vec![
         ("start_date", start_date.to_string()),
         ("end_date", end_date.to_string()),
-        ("name", other_data[0].to_owned()),
-        ("created_at", other_data[1].to_owned()),
-        ("version", other_data[2].to_owned()),
-        ("provider", other_data[3].to_owned()),
     ];
+    // The values of the third row are optional.
+    for key in ["name", "created_at", "version", "provider"] {
+        if let Some(value) = other_data.next() {
+            rows.push((key, value));
+        }
+    }
 
     let data: Vec<TimetableMetadataEntry> = rows.iter()
         .map(|(key, value)| {
@@ -134,3 +136,52 @@ fn create_instance(
 pub fn parse(path: &str) -> Result<ResourceStorage<TimetableMetadataEntry>, Box<dyn Error>> {
     TimetableMetadataParser::new().parse(path)
 }
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::utils::timetable_info;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn timetable_info_v207() {
+        let rows = vec![
+            "15.12.2024".to_string(),
+            "13.12.2025".to_string(),
+            "Fahrplan 2025$02.12.2024 15:23:54$5.40.41$INFO+".to_string(),
+        ];
+        let timetable_metadata_parser = TimetableMetadataParser::new();
+        let parser = FileParser {
+            row_parser: timetable_metadata_parser.row_parser.clone(),
+            rows,
+        };
+        let timetable_metadata = ResourceStorage::new(row_converter(parser).unwrap());
+        let timetable_info = timetable_info(&timetable_metadata).unwrap();
+        let period = timetable_info.period();
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+            period.start_date()
+        );
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2025, 12, 13).unwrap(),
+            period.end_date()
+        );
+        assert_eq!(364, period.num_days());
+        assert_eq!("Fahrplan 2025", timetable_info.name());
+        assert_eq!(
+            "2024-12-02 15:23:54",
+            timetable_info.created_at().unwrap().to_string()
+        );
+        assert_eq!("5.40.41", timetable_info.version());
+        assert_eq!("INFO+", timetable_info.provider());
+    }
+
+    #[test]
+    fn missing_end_date_v207() {
+        let timetable_metadata = ResourceStorage::new(TimetableMetadataEntry::vec_to_map(vec![
+            TimetableMetadataEntry::new(1, "start_date".to_string(), "2024-12-15".to_string()),
+        ]));
+        assert!(timetable_info(&timetable_metadata).is_err());
+    }
+}
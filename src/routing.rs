@@ -0,0 +1,405 @@
+// ------------------------------------------------------------------------------------------------
+// --- Journey planning
+// ------------------------------------------------------------------------------------------------
+
+mod csa;
+mod matrix;
+mod raptor;
+mod reachability;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+
+use crate::{
+    models::{Attribute, Journey, Platform},
+    storage::DataStorage,
+};
+
+pub use csa::ConnectionTable;
+pub use matrix::TravelTimeMatrix;
+pub use reachability::Reachability;
+
+// ------------------------------------------------------------------------------------------------
+// --- Itinerary
+// ------------------------------------------------------------------------------------------------
+
+/// A way to travel from a stop to another, made of ride and walk legs.
+#[derive(Debug)]
+pub struct Itinerary<'a> {
+    legs: Vec<Leg<'a>>,
+}
+
+impl<'a> Itinerary<'a> {
+    pub fn new(legs: Vec<Leg<'a>>) -> Self {
+        Self { legs }
+    }
+
+    // Getters/Setters
+
+    pub fn legs(&self) -> &Vec<Leg<'a>> {
+        &self.legs
+    }
+
+    // Functions
+
+    pub fn departure_time(&self) -> Option<NaiveDateTime> {
+        self.legs.first().map(Leg::departure_time)
+    }
+
+    pub fn arrival_time(&self) -> Option<NaiveDateTime> {
+        self.legs.last().map(Leg::arrival_time)
+    }
+
+    pub fn duration(&self) -> TimeDelta {
+        match (self.departure_time(), self.arrival_time()) {
+            (Some(departure_time), Some(arrival_time)) => arrival_time - departure_time,
+            _ => TimeDelta::zero(),
+        }
+    }
+
+    /// Number of times the passenger changes vehicle. Staying on board through a through service is not a transfer.
+    pub fn transfer_count(&self) -> usize {
+        self.legs
+            .iter()
+            .filter(|leg| matches!(leg, Leg::Ride(ride) if !ride.is_through_service()))
+            .count()
+            .saturating_sub(1)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Leg
+// ------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub enum Leg<'a> {
+    Ride(RideLeg<'a>),
+    Walk(WalkLeg),
+}
+
+impl Leg<'_> {
+    pub fn departure_time(&self) -> NaiveDateTime {
+        match self {
+            Self::Ride(ride) => ride.departure_time(),
+            Self::Walk(walk) => walk.departure_time(),
+        }
+    }
+
+    pub fn arrival_time(&self) -> NaiveDateTime {
+        match self {
+            Self::Ride(ride) => ride.arrival_time(),
+            Self::Walk(walk) => walk.arrival_time(),
+        }
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- RideLeg
+// ------------------------------------------------------------------------------------------------
+
+/// Part of a journey between the boarding and the alighting stops.
+#[derive(Debug)]
+pub struct RideLeg<'a> {
+    journey: &'a Journey,
+    service_date: NaiveDate, // Date of the route's first entry.
+    departure_index: usize,  // Position of the boarding stop in the journey's route.
+    arrival_index: usize,    // Position of the alighting stop in the journey's route.
+    departure_time: NaiveDateTime,
+    arrival_time: NaiveDateTime,
+    departure_platform: Option<&'a Platform>,
+    arrival_platform: Option<&'a Platform>,
+    is_through_service: bool, // True if the passenger stays on board from the previous ride (DURCHBI file).
+}
+
+impl<'a> RideLeg<'a> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        journey: &'a Journey,
+        service_date: NaiveDate,
+        departure_index: usize,
+        arrival_index: usize,
+        departure_time: NaiveDateTime,
+        arrival_time: NaiveDateTime,
+        departure_platform: Option<&'a Platform>,
+        arrival_platform: Option<&'a Platform>,
+        is_through_service: bool,
+    ) -> Self {
+        Self {
+            journey,
+            service_date,
+            departure_index,
+            arrival_index,
+            departure_time,
+            arrival_time,
+            departure_platform,
+            arrival_platform,
+            is_through_service,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn journey(&self) -> &'a Journey {
+        self.journey
+    }
+
+    pub fn service_date(&self) -> NaiveDate {
+        self.service_date
+    }
+
+    pub fn departure_index(&self) -> usize {
+        self.departure_index
+    }
+
+    pub fn arrival_index(&self) -> usize {
+        self.arrival_index
+    }
+
+    pub fn departure_time(&self) -> NaiveDateTime {
+        self.departure_time
+    }
+
+    pub fn arrival_time(&self) -> NaiveDateTime {
+        self.arrival_time
+    }
+
+    pub fn departure_platform(&self) -> Option<&'a Platform> {
+        self.departure_platform
+    }
+
+    pub fn arrival_platform(&self) -> Option<&'a Platform> {
+        self.arrival_platform
+    }
+
+    pub fn is_through_service(&self) -> bool {
+        self.is_through_service
+    }
+
+    // Functions
+
+    pub fn departure_stop_id(&self) -> i32 {
+        self.journey.route()[self.departure_index].stop_id()
+    }
+
+    pub fn arrival_stop_id(&self) -> i32 {
+        self.journey.route()[self.arrival_index].stop_id()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- WalkLeg
+// ------------------------------------------------------------------------------------------------
+
+/// Walk between two stops (METABHF file).
+#[derive(Debug)]
+pub struct WalkLeg {
+    departure_stop_id: i32,
+    arrival_stop_id: i32,
+    departure_time: NaiveDateTime,
+    arrival_time: NaiveDateTime,
+    attribute_ids: Vec<i32>, // Attributes of the stop connection, e.g. accessible or stairs.
+}
+
+impl WalkLeg {
+    pub fn new(
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        departure_time: NaiveDateTime,
+        arrival_time: NaiveDateTime,
+        attribute_ids: Vec<i32>,
+    ) -> Self {
+        Self {
+            departure_stop_id,
+            arrival_stop_id,
+            departure_time,
+            arrival_time,
+            attribute_ids,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn departure_stop_id(&self) -> i32 {
+        self.departure_stop_id
+    }
+
+    pub fn arrival_stop_id(&self) -> i32 {
+        self.arrival_stop_id
+    }
+
+    pub fn departure_time(&self) -> NaiveDateTime {
+        self.departure_time
+    }
+
+    pub fn arrival_time(&self) -> NaiveDateTime {
+        self.arrival_time
+    }
+
+    pub fn attribute_ids(&self) -> &Vec<i32> {
+        &self.attribute_ids
+    }
+
+    // Functions
+
+    pub fn attributes<'a>(&self, data_storage: &'a DataStorage) -> Vec<&'a Attribute> {
+        self.attribute_ids
+            .iter()
+            .filter_map(|&id| data_storage.attributes().find(id))
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+fn minutes_of_day(time: NaiveTime) -> i32 {
+    (time.num_seconds_from_midnight() / 60) as i32
+}
+
+fn walk_attribute_ids(data_storage: &DataStorage, stop_connection_id: i32) -> Vec<i32> {
+    data_storage
+        .stop_connections()
+        .find(stop_connection_id)
+        .and_then(|stop_connection| stop_connection.attribute_id())
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::storage::tests::data_storage_from;
+
+    /// Small network shared by the routing tests:
+    /// - IC 1: A 08:00, B 08:30/08:32, C 09:00
+    /// - B 2: C 09:10, D 09:30
+    /// - B 3: B 08:35, C 08:50
+    /// - B 4: F 08:40, G 09:00
+    /// - B 5: H 23:30, I 00:10/00:12, J 00:40 (after midnight), only on 03.03.2025
+    /// - B 6: K 10:00, M 11:00
+    /// - B 7: K 10:15, L 10:30
+    /// - B 8: L 10:35, M 11:10
+    /// - B 9: K 09:50, M 11:20
+    /// - B 10: N 12:00, O 12:30, continuing into B 11: O 12:31, P 13:00 (through service)
+    /// - Footpath from B to F, 2 minutes.
+    pub(crate) fn data_storage() -> DataStorage {
+        data_storage_from(&[
+            (
+                "BAHNHOF",
+                &[
+                    "8500010     A$<1>",
+                    "8500020     B$<1>",
+                    "8500030     C$<1>",
+                    "8500040     D$<1>",
+                    "8500060     F$<1>",
+                    "8500070     G$<1>",
+                    "8500080     H$<1>",
+                    "8500090     I$<1>",
+                    "8500100     J$<1>",
+                    "8500110     K$<1>",
+                    "8500120     L$<1>",
+                    "8500130     M$<1>",
+                    "8500140     N$<1>",
+                    "8500150     O$<1>",
+                    "8500160     P$<1>",
+                ],
+            ),
+            (
+                "BITFELD",
+                &[
+                    "000001 000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000",
+                ],
+            ),
+            (
+                "DURCHBI",
+                &["000010 000011 8500150 000011 000011 000000 8500150"],
+            ),
+            ("METABHF", &["8500020 8500060 002"]),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G IC  8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000000                               %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00830  00832                 %",
+                    "8500030 C                     00900                        %",
+                    "*Z 000002 000011                                           %",
+                    "*G B   8500030 8500040                                     %",
+                    "*A VE 8500030 8500040 000000                               %",
+                    "8500030 C                            00910                 %",
+                    "8500040 D                     00930                        %",
+                    "*Z 000003 000011                                           %",
+                    "*G B   8500020 8500030                                     %",
+                    "*A VE 8500020 8500030 000000                               %",
+                    "8500020 B                            00835                 %",
+                    "8500030 C                     00850                        %",
+                    "*Z 000004 000011                                           %",
+                    "*G B   8500060 8500070                                     %",
+                    "*A VE 8500060 8500070 000000                               %",
+                    "8500060 F                            00840                 %",
+                    "8500070 G                     00900                        %",
+                    "*Z 000005 000011                                           %",
+                    "*G B   8500080 8500100                                     %",
+                    "*A VE 8500080 8500100 000001                               %",
+                    "8500080 H                            02330                 %",
+                    "8500090 I                     02410  02412                 %",
+                    "8500100 J                     02440                        %",
+                    "*Z 000006 000011                                           %",
+                    "*G B   8500110 8500130                                     %",
+                    "*A VE 8500110 8500130 000000                               %",
+                    "8500110 K                            01000                 %",
+                    "8500130 M                     01100                        %",
+                    "*Z 000007 000011                                           %",
+                    "*G B   8500110 8500120                                     %",
+                    "*A VE 8500110 8500120 000000                               %",
+                    "8500110 K                            01015                 %",
+                    "8500120 L                     01030                        %",
+                    "*Z 000008 000011                                           %",
+                    "*G B   8500120 8500130                                     %",
+                    "*A VE 8500120 8500130 000000                               %",
+                    "8500120 L                            01035                 %",
+                    "8500130 M                     01110                        %",
+                    "*Z 000009 000011                                           %",
+                    "*G B   8500110 8500130                                     %",
+                    "*A VE 8500110 8500130 000000                               %",
+                    "8500110 K                            00950                 %",
+                    "8500130 M                     01120                        %",
+                    "*Z 000010 000011                                           %",
+                    "*G B   8500140 8500150                                     %",
+                    "*A VE 8500140 8500150 000000                               %",
+                    "8500140 N                            01200                 %",
+                    "8500150 O                     01230                        %",
+                    "*Z 000011 000011                                           %",
+                    "*G B   8500150 8500160                                     %",
+                    "*A VE 8500150 8500160 000000                               %",
+                    "8500150 O                            01231                 %",
+                    "8500160 P                     01300                        %",
+                ],
+            ),
+        ])
+    }
+
+    /// Describes each leg as "ride|walk departure_stop_id HH:MM arrival_stop_id HH:MM".
+    pub(crate) fn describe(itinerary: &Itinerary) -> Vec<String> {
+        itinerary
+            .legs()
+            .iter()
+            .map(|leg| {
+                let (kind, departure_stop_id, arrival_stop_id) = match leg {
+                    Leg::Ride(ride) => ("ride", ride.departure_stop_id(), ride.arrival_stop_id()),
+                    Leg::Walk(walk) => ("walk", walk.departure_stop_id(), walk.arrival_stop_id()),
+                };
+                format!(
+                    "{kind} {departure_stop_id} {} {arrival_stop_id} {}",
+                    leg.departure_time().format("%H:%M"),
+                    leg.arrival_time().format("%H:%M"),
+                )
+            })
+            .collect()
+    }
+
+    pub(crate) fn date_time(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M").unwrap()
+    }
+}
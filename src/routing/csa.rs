@@ -0,0 +1,800 @@
+// ------------------------------------------------------------------------------------------------
+// --- Profile queries (Connection Scan Algorithm)
+// ------------------------------------------------------------------------------------------------
+//
+// The connections (a journey going from a stop to the next one) are sorted by departure time
+// and scanned once, from the latest to the earliest, to compute for each stop the Pareto set
+// of (departure time, arrival time at the target).
+// See J. Dibbelt, T. Pajor, B. Strasser and D. Wagner, "Connection Scan Algorithm" (2017).
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    footpath::StopConnectionView,
+    models::{Journey, Model},
+    routing::{Itinerary, Leg, RideLeg, WalkLeg, minutes_of_day, walk_attribute_ids},
+    storage::DataStorage,
+    utils::sub_1_day,
+};
+
+impl DataStorage {
+    /// Returns the connections of the date, ready for the profile queries.
+    /// The journeys of the previous day still running after midnight are included.
+    pub fn connection_table(&self, date: NaiveDate) -> ConnectionTable<'_> {
+        ConnectionTable::new(self, date)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- ConnectionTable
+// ------------------------------------------------------------------------------------------------
+
+/// A journey going from a stop to the next one. The times are in minutes since the midnight of the table's date.
+#[derive(Debug)]
+struct Connection {
+    trip: usize,            // Index in ConnectionTable::trips.
+    departure_index: usize, // Position of the departure stop in the journey's route, the arrival stop is the next one.
+    departure_stop_id: i32,
+    arrival_stop_id: i32,
+    departure_time: i32,
+    arrival_time: i32,
+    boarding_allowed: bool,
+    alighting_allowed: bool,
+}
+
+/// Best known way to reach the target by leaving a stop at departure_time.
+#[derive(Clone, Copy, Debug)]
+struct ProfileEntry {
+    departure_time: i32,
+    arrival_time: i32,
+    enter: usize, // Connection where the journey is boarded.
+    exit: usize,  // Connection after which the journey is left.
+}
+
+/// The passengers of a trip can stay on board into another trip (through service).
+#[derive(Clone, Copy, Debug)]
+struct ThroughLink {
+    arrival_index: usize, // Position of the stop where the passengers leave the trip in its journey's route.
+    trip: usize,
+    departure_index: usize, // Position of the stop where the passengers get on the next trip in its journey's route.
+}
+
+/// What the passenger does after leaving a journey.
+#[derive(Clone, Copy, Debug)]
+enum Continuation {
+    Arrived,
+    /// Walk to the target.
+    Walk {
+        arrival_stop_id: i32,
+        duration: i32,
+        stop_connection_id: i32,
+    },
+    Transfer {
+        stop_id: i32,
+        walk: Option<(i32, i32)>, // (duration, stop_connection_id), None if the next journey leaves from the same stop.
+        entry: usize,             // Index in the profile of the stop.
+    },
+}
+
+/// The connections of a date, sorted by departure time.
+#[derive(Debug)]
+pub struct ConnectionTable<'a> {
+    data_storage: &'a DataStorage,
+    date: NaiveDate,
+    trips: Vec<(i32, NaiveDate, i32)>, // (journey_id, service_date, departure time at the first stop)
+    through_links: Vec<Vec<ThroughLink>>, // For each trip, the trips its passengers can stay on board into.
+    connections: Vec<Connection>,
+    footpaths: FxHashMap<i32, Vec<(i32, i32, i32)>>, // stop_id => (arrival stop_id, duration, stop_connection_id)
+}
+
+impl<'a> ConnectionTable<'a> {
+    fn new(data_storage: &'a DataStorage, date: NaiveDate) -> Self {
+        let mut trips = Vec::new();
+        let mut connections = Vec::new();
+
+        for service_date in [sub_1_day(date), date] {
+            let day_offset = (service_date - date).num_days() as i32 * 24 * 60;
+
+            for journey in data_storage.journeys().entries() {
+                if !journey.operates_on(service_date, data_storage) {
+                    continue;
+                }
+
+                // unwrap: The first route entry always has a departure time.
+                let start =
+                    day_offset + minutes_of_day(journey.route()[0].departure_time().unwrap());
+                let offsets = journey.stop_offsets();
+                let trip = trips.len();
+                trips.push((journey.id(), service_date, start));
+
+                for (i, route_entries) in journey.route().windows(2).enumerate() {
+                    let (departure, arrival) = (&route_entries[0], &route_entries[1]);
+                    let (Some(departure_offset), Some(arrival_offset)) = (
+                        offsets[i].1.or(offsets[i].0),
+                        offsets[i + 1].0.or(offsets[i + 1].1),
+                    ) else {
+                        continue;
+                    };
+                    let departure_time = start + departure_offset;
+                    if departure_time < 0 {
+                        // Already covered by the table of the previous day.
+                        continue;
+                    }
+
+                    connections.push(Connection {
+                        trip,
+                        departure_index: i,
+                        departure_stop_id: departure.stop_id(),
+                        arrival_stop_id: arrival.stop_id(),
+                        departure_time,
+                        arrival_time: start + arrival_offset,
+                        boarding_allowed: departure.boarding_allowed()
+                            && journey.serves_stop_on(
+                                departure.stop_id(),
+                                service_date,
+                                data_storage,
+                            ),
+                        alighting_allowed: arrival.alighting_allowed()
+                            && journey.serves_stop_on(
+                                arrival.stop_id(),
+                                service_date,
+                                data_storage,
+                            ),
+                    });
+                }
+            }
+        }
+
+        connections.sort_by_key(|connection| {
+            (
+                connection.departure_time,
+                connection.arrival_time,
+                connection.trip,
+                connection.departure_index,
+            )
+        });
+
+        let through_links = create_through_links(data_storage, &trips);
+        let footpaths = create_footpaths(data_storage);
+
+        Self {
+            data_storage,
+            date,
+            trips,
+            through_links,
+            connections,
+            footpaths,
+        }
+    }
+
+    // Getters/Setters
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    // Functions
+
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    /// Returns the Pareto-optimal itineraries leaving the departure stop between from and until (both included):
+    /// no other itinerary leaves later and arrives earlier. They are sorted by departure time.
+    ///
+    /// The minimum exchange times (see DataStorage::exchange_time), the boarding and alighting restrictions
+    /// and the stop connections (METABHF file) are taken into account.
+    pub fn profile(
+        &self,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        from: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Vec<Itinerary<'a>> {
+        let (from, until) = (self.to_minutes(from), self.to_minutes(until));
+        self.itineraries(departure_stop_id, arrival_stop_id, from, until, None)
+    }
+
+    /// Returns the itinerary leaving the departure stop as late as possible to reach the arrival stop by arrive_by
+    /// ("arrive by" query), None if there is none on the table's date.
+    pub fn latest_departure(
+        &self,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        arrive_by: NaiveDateTime,
+    ) -> Option<Itinerary<'a>> {
+        let arrive_by = self.to_minutes(arrive_by);
+        // The latest departure is the last Pareto-optimal itinerary arriving in time.
+        self.itineraries(
+            departure_stop_id,
+            arrival_stop_id,
+            i32::MIN,
+            arrive_by,
+            Some(arrive_by),
+        )
+        .pop()
+    }
+
+    fn itineraries(
+        &self,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        from: i32,
+        until: i32,
+        arrival_limit: Option<i32>,
+    ) -> Vec<Itinerary<'a>> {
+        if departure_stop_id == arrival_stop_id {
+            return Vec::new();
+        }
+
+        let profiles = self.scan(arrival_stop_id, from, arrival_limit);
+
+        // The departure stop can be left on foot.
+        let mut starts: Vec<_> = self
+            .stop_connections(departure_stop_id)
+            .iter()
+            .map(|&(stop_id, duration, stop_connection_id)| {
+                (stop_id, Some((duration, stop_connection_id)))
+            })
+            .chain([(departure_stop_id, None)])
+            .flat_map(|(stop_id, walk)| {
+                let entries = profiles.get(&stop_id).map_or(&[][..], Vec::as_slice);
+                entries.iter().enumerate().map(move |(i, entry)| {
+                    let walk_duration = walk.map_or(0, |(duration, _)| duration);
+                    (
+                        entry.departure_time - walk_duration,
+                        entry.arrival_time,
+                        stop_id,
+                        walk,
+                        i,
+                    )
+                })
+            })
+            .filter(|&(departure_time, ..)| from <= departure_time && departure_time <= until)
+            .collect();
+
+        // Pareto filter, from the latest departure.
+        starts.retain(|&(_, arrival_time, ..)| {
+            arrival_limit.is_none_or(|limit| arrival_time <= limit)
+        });
+        starts.sort_by_key(|&(departure_time, arrival_time, ..)| {
+            (std::cmp::Reverse(departure_time), arrival_time)
+        });
+        let mut best_arrival_time = i32::MAX;
+        starts.retain(|&(_, arrival_time, ..)| {
+            let is_optimal = arrival_time < best_arrival_time;
+            best_arrival_time = best_arrival_time.min(arrival_time);
+            is_optimal
+        });
+        starts.reverse();
+
+        starts
+            .into_iter()
+            .map(|(departure_time, _, stop_id, walk, entry)| {
+                let mut legs = Vec::new();
+                if let Some((duration, stop_connection_id)) = walk {
+                    legs.push(self.walk_leg(
+                        departure_stop_id,
+                        stop_id,
+                        departure_time,
+                        duration,
+                        stop_connection_id,
+                    ));
+                }
+                self.push_legs(&profiles, arrival_stop_id, stop_id, entry, &mut legs);
+                Itinerary::new(legs)
+            })
+            .collect()
+    }
+
+    /// Computes the profile of each stop, scanning the connections leaving from from.
+    /// The connections arriving after arrival_limit are ignored.
+    fn scan(
+        &self,
+        target_stop_id: i32,
+        from: i32,
+        arrival_limit: Option<i32>,
+    ) -> FxHashMap<i32, Vec<ProfileEntry>> {
+        // For each stop, the entries are sorted by decreasing departure time (and arrival time).
+        let mut profiles: FxHashMap<i32, Vec<ProfileEntry>> = FxHashMap::default();
+        // For each trip, the earliest arrival at the target when staying on board and where to leave.
+        let mut trips: Vec<Option<(i32, usize)>> = vec![None; self.trips.len()];
+
+        for (c, connection) in self.connections.iter().enumerate().rev() {
+            if connection.departure_time < from {
+                break;
+            }
+            if arrival_limit.is_some_and(|limit| connection.arrival_time > limit) {
+                continue;
+            }
+
+            let mut best = trips[connection.trip];
+            if connection.alighting_allowed
+                && let Some((arrival_time, _)) = self.continuation(&profiles, target_stop_id, c)
+                && best.is_none_or(|(best_arrival_time, _)| arrival_time < best_arrival_time)
+            {
+                best = Some((arrival_time, c));
+            }
+            for link in &self.through_links[connection.trip] {
+                if connection.departure_index + 1 != link.arrival_index {
+                    continue;
+                }
+                // The passengers must leave the next trip after getting on it.
+                if let Some((arrival_time, exit)) = trips[link.trip]
+                    && (self.connections[exit].trip != link.trip
+                        || self.connections[exit].departure_index >= link.departure_index)
+                    && best.is_none_or(|(best_arrival_time, _)| arrival_time < best_arrival_time)
+                {
+                    best = Some((arrival_time, exit));
+                }
+            }
+            let Some((arrival_time, exit)) = best else {
+                continue;
+            };
+            trips[connection.trip] = best;
+
+            if !connection.boarding_allowed || connection.departure_stop_id == target_stop_id {
+                continue;
+            }
+            let entries = profiles.entry(connection.departure_stop_id).or_default();
+            let entry = ProfileEntry {
+                departure_time: connection.departure_time,
+                arrival_time,
+                enter: c,
+                exit,
+            };
+            match entries.last() {
+                Some(last) if arrival_time >= last.arrival_time => {}
+                Some(last) if last.departure_time == entry.departure_time => {
+                    *entries.last_mut().unwrap() = entry;
+                }
+                _ => entries.push(entry),
+            }
+        }
+
+        profiles
+    }
+
+    /// Returns the earliest arrival at the target after leaving the journey at the end of the connection, and how.
+    fn continuation(
+        &self,
+        profiles: &FxHashMap<i32, Vec<ProfileEntry>>,
+        target_stop_id: i32,
+        c: usize,
+    ) -> Option<(i32, Continuation)> {
+        let connection = &self.connections[c];
+        let stop_id = connection.arrival_stop_id;
+        if stop_id == target_stop_id {
+            return Some((connection.arrival_time, Continuation::Arrived));
+        }
+
+        let mut best: Option<(i32, Continuation)> = None;
+        let mut consider = |arrival_time: i32, continuation| {
+            if best.is_none_or(|(best_arrival_time, _)| arrival_time < best_arrival_time) {
+                best = Some((arrival_time, continuation));
+            }
+        };
+
+        // Same stop, the minimum exchange time applies.
+        if let Some(entries) = profiles.get(&stop_id) {
+            let (journey, service_date) = self.trip(connection.trip);
+            let count =
+                entries.partition_point(|entry| entry.departure_time >= connection.arrival_time);
+            let found = (0..count).rev().find(|&i| {
+                let next_connection = &self.connections[entries[i].enter];
+                if next_connection.trip == connection.trip {
+                    return false;
+                }
+                let (next_journey, _) = self.trip(next_connection.trip);
+                let (exchange_time, _) = self.data_storage.exchange_time(
+                    journey,
+                    connection.departure_index + 1,
+                    next_journey,
+                    next_connection.departure_index,
+                    service_date,
+                );
+                entries[i].departure_time >= connection.arrival_time + i32::from(exchange_time)
+            });
+            if let Some(i) = found {
+                consider(
+                    entries[i].arrival_time,
+                    Continuation::Transfer {
+                        stop_id,
+                        walk: None,
+                        entry: i,
+                    },
+                );
+            }
+        }
+
+        // Walk to the target or to another stop.
+        for &(next_stop_id, duration, stop_connection_id) in self.stop_connections(stop_id) {
+            let ready_time = connection.arrival_time + duration;
+            if next_stop_id == target_stop_id {
+                consider(
+                    ready_time,
+                    Continuation::Walk {
+                        arrival_stop_id: next_stop_id,
+                        duration,
+                        stop_connection_id,
+                    },
+                );
+                continue;
+            }
+
+            let Some(entries) = profiles.get(&next_stop_id) else {
+                continue;
+            };
+            let count = entries.partition_point(|entry| entry.departure_time >= ready_time);
+            if count > 0 {
+                consider(
+                    entries[count - 1].arrival_time,
+                    Continuation::Transfer {
+                        stop_id: next_stop_id,
+                        walk: Some((duration, stop_connection_id)),
+                        entry: count - 1,
+                    },
+                );
+            }
+        }
+
+        best
+    }
+
+    /// Adds the legs of the itinerary starting with the profile entry of the stop.
+    fn push_legs(
+        &self,
+        profiles: &FxHashMap<i32, Vec<ProfileEntry>>,
+        target_stop_id: i32,
+        stop_id: i32,
+        entry: usize,
+        legs: &mut Vec<Leg<'a>>,
+    ) {
+        let mut entry = profiles[&stop_id][entry];
+
+        loop {
+            let (enter, exit) = (
+                &self.connections[entry.enter],
+                &self.connections[entry.exit],
+            );
+            for (i, (trip, departure_index, arrival_index)) in
+                self.segments(enter, exit).into_iter().enumerate()
+            {
+                legs.push(self.ride_leg(trip, departure_index, arrival_index, i > 0));
+            }
+
+            match self.continuation(profiles, target_stop_id, entry.exit) {
+                None | Some((_, Continuation::Arrived)) => break,
+                Some((
+                    _,
+                    Continuation::Walk {
+                        arrival_stop_id,
+                        duration,
+                        stop_connection_id,
+                    },
+                )) => {
+                    legs.push(self.walk_leg(
+                        exit.arrival_stop_id,
+                        arrival_stop_id,
+                        exit.arrival_time,
+                        duration,
+                        stop_connection_id,
+                    ));
+                    break;
+                }
+                Some((
+                    _,
+                    Continuation::Transfer {
+                        stop_id,
+                        walk,
+                        entry: next,
+                    },
+                )) => {
+                    if let Some((duration, stop_connection_id)) = walk {
+                        legs.push(self.walk_leg(
+                            exit.arrival_stop_id,
+                            stop_id,
+                            exit.arrival_time,
+                            duration,
+                            stop_connection_id,
+                        ));
+                    }
+                    entry = profiles[&stop_id][next];
+                }
+            }
+        }
+    }
+
+    /// Returns the (arrival stop_id, duration, stop_connection_id) of the stop connections leaving from the stop.
+    fn stop_connections(&self, stop_id: i32) -> &[(i32, i32, i32)] {
+        self.footpaths.get(&stop_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the (trip, departure index, arrival index) ridden from the enter connection to the exit connection.
+    /// There are several of them if the passenger stays on board through a through service.
+    fn segments(&self, enter: &Connection, exit: &Connection) -> Vec<(usize, usize, usize)> {
+        // unwrap: The exit connection is always reachable from the enter connection, see ConnectionTable::scan.
+        let links = self.through_path(enter.trip, exit.trip).unwrap();
+        let (mut trip, mut departure_index) = (enter.trip, enter.departure_index);
+        let mut segments = Vec::new();
+
+        for link in links {
+            segments.push((trip, departure_index, link.arrival_index));
+            (trip, departure_index) = (link.trip, link.departure_index);
+        }
+        segments.push((trip, departure_index, exit.departure_index + 1));
+        segments
+    }
+
+    /// Returns the through links leading from a trip to another, None if there are none.
+    fn through_path(&self, from: usize, to: usize) -> Option<Vec<ThroughLink>> {
+        if from == to {
+            return Some(Vec::new());
+        }
+        self.through_links[from].iter().find_map(|&link| {
+            let mut path = self.through_path(link.trip, to)?;
+            path.insert(0, link);
+            Some(path)
+        })
+    }
+
+    fn ride_leg(
+        &self,
+        trip: usize,
+        departure_index: usize,
+        arrival_index: usize,
+        is_through_service: bool,
+    ) -> Leg<'a> {
+        let data_storage = self.data_storage;
+        let (journey, service_date) = self.trip(trip);
+        let start = self.trips[trip].2;
+        let offsets = journey.stop_offsets();
+        // unwrap: The connections are only created between the entries having times.
+        let departure_offset = offsets[departure_index]
+            .1
+            .or(offsets[departure_index].0)
+            .unwrap();
+        let arrival_offset = offsets[arrival_index]
+            .0
+            .or(offsets[arrival_index].1)
+            .unwrap();
+
+        Leg::Ride(RideLeg::new(
+            journey,
+            service_date,
+            departure_index,
+            arrival_index,
+            self.to_date_time(start + departure_offset),
+            self.to_date_time(start + arrival_offset),
+            journey.platform_at(departure_index, service_date, data_storage),
+            journey.platform_at(arrival_index, service_date, data_storage),
+            is_through_service,
+        ))
+    }
+
+    fn trip(&self, trip: usize) -> (&'a Journey, NaiveDate) {
+        let (journey_id, service_date, _) = self.trips[trip];
+        // unwrap: The journeys of the table always exist.
+        (
+            self.data_storage.journeys().find(journey_id).unwrap(),
+            service_date,
+        )
+    }
+
+    fn walk_leg(
+        &self,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        departure_time: i32,
+        duration: i32,
+        stop_connection_id: i32,
+    ) -> Leg<'a> {
+        Leg::Walk(WalkLeg::new(
+            departure_stop_id,
+            arrival_stop_id,
+            self.to_date_time(departure_time),
+            self.to_date_time(departure_time + duration),
+            walk_attribute_ids(self.data_storage, stop_connection_id),
+        ))
+    }
+
+    fn to_minutes(&self, date_time: NaiveDateTime) -> i32 {
+        (date_time - NaiveDateTime::new(self.date, NaiveTime::MIN)).num_minutes() as i32
+    }
+
+    fn to_date_time(&self, minutes: i32) -> NaiveDateTime {
+        NaiveDateTime::new(self.date, NaiveTime::MIN) + TimeDelta::minutes(minutes.into())
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Returns the stop connections leaving from each stop, see ConnectionTable::stop_connections.
+fn create_footpaths(data_storage: &DataStorage) -> FxHashMap<i32, Vec<(i32, i32, i32)>> {
+    data_storage
+        .stop_connections_by_stop_id()
+        .keys()
+        .map(|&stop_id| {
+            let footpaths = data_storage
+                .footpaths(stop_id, StopConnectionView::Directed)
+                .into_iter()
+                .map(|footpath| {
+                    (
+                        footpath.arrival_stop_id(),
+                        i32::from(footpath.duration()),
+                        footpath.stop_connection_ids()[0],
+                    )
+                })
+                .collect();
+            (stop_id, footpaths)
+        })
+        .collect()
+}
+
+fn create_through_links(
+    data_storage: &DataStorage,
+    trips: &[(i32, NaiveDate, i32)],
+) -> Vec<Vec<ThroughLink>> {
+    let trip_by_journey: FxHashMap<_, _> = trips
+        .iter()
+        .enumerate()
+        .map(|(trip, &(journey_id, service_date, _))| ((journey_id, service_date), trip))
+        .collect();
+
+    trips
+        .iter()
+        .map(|&(journey_id, service_date, _)| {
+            if !data_storage
+                .through_services_by_journey_id()
+                .contains_key(&journey_id)
+            {
+                return Vec::new();
+            }
+            // unwrap: The journeys of the table always exist.
+            let journey = data_storage.journeys().find(journey_id).unwrap();
+
+            data_storage
+                .through_journeys(journey, service_date)
+                .into_iter()
+                .filter_map(|through_journey| {
+                    let arrival_index = journey.route().iter().rposition(|route_entry| {
+                        route_entry.stop_id() == through_journey.from_stop_id()
+                    })?;
+                    let key = (
+                        through_journey.journey().id(),
+                        through_journey.service_date(),
+                    );
+                    Some(ThroughLink {
+                        arrival_index,
+                        trip: *trip_by_journey.get(&key)?,
+                        departure_index: through_journey.departure_index(),
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::tests::{data_storage, date_time, describe};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn pareto_profile_v207() {
+        let data_storage = data_storage();
+        let connection_table =
+            data_storage.connection_table(NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+
+        // Leaving later needs a transfer and arrives later, the journey 9 is dominated by the journey 6.
+        let itineraries = connection_table.profile(
+            8500110,
+            8500130,
+            date_time("2025-03-03", "09:00"),
+            date_time("2025-03-03", "11:00"),
+        );
+        let itineraries: Vec<_> = itineraries
+            .iter()
+            .map(|itinerary| (describe(itinerary), itinerary.transfer_count()))
+            .collect();
+        assert_eq!(
+            vec![
+                (vec!["ride 8500110 10:00 8500130 11:00".to_string()], 0),
+                (
+                    vec![
+                        "ride 8500110 10:15 8500120 10:30".to_string(),
+                        "ride 8500120 10:35 8500130 11:10".to_string(),
+                    ],
+                    1
+                ),
+            ],
+            itineraries
+        );
+
+        // Changing at B arrives earlier than staying on board, for the same departure.
+        let itineraries = connection_table.profile(
+            8500010,
+            8500030,
+            date_time("2025-03-03", "07:00"),
+            date_time("2025-03-03", "09:00"),
+        );
+        assert_eq!(1, itineraries.len());
+        assert_eq!(
+            vec![
+                "ride 8500010 08:00 8500020 08:30",
+                "ride 8500020 08:35 8500030 08:50",
+            ],
+            describe(&itineraries[0])
+        );
+    }
+
+    #[test]
+    fn latest_departure_with_footpath_v207() {
+        let data_storage = data_storage();
+        let connection_table =
+            data_storage.connection_table(NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+
+        let itinerary = connection_table
+            .latest_departure(8500010, 8500070, date_time("2025-03-03", "09:00"))
+            .unwrap();
+        assert_eq!(
+            vec![
+                "ride 8500010 08:00 8500020 08:30",
+                "walk 8500020 08:30 8500060 08:32",
+                "ride 8500060 08:40 8500070 09:00",
+            ],
+            describe(&itinerary)
+        );
+        let Leg::Walk(walk_leg) = &itinerary.legs()[1] else {
+            panic!("The second leg must be a walk.");
+        };
+        assert!(walk_leg.attribute_ids().is_empty());
+
+        assert!(
+            connection_table
+                .latest_departure(8500010, 8500070, date_time("2025-03-03", "08:59"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn through_service_v207() {
+        let data_storage = data_storage();
+        let connection_table =
+            data_storage.connection_table(NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+
+        // The passengers stay on board at O, 1 minute is enough.
+        let itinerary = connection_table
+            .latest_departure(8500140, 8500160, date_time("2025-03-03", "13:00"))
+            .unwrap();
+        assert_eq!(
+            vec![
+                "ride 8500140 12:00 8500150 12:30",
+                "ride 8500150 12:31 8500160 13:00",
+            ],
+            describe(&itinerary)
+        );
+        assert_eq!(0, itinerary.transfer_count());
+        let Leg::Ride(ride_leg) = &itinerary.legs()[1] else {
+            panic!("The second leg must be a ride.");
+        };
+        assert!(ride_leg.is_through_service());
+
+        let itineraries = connection_table.profile(
+            8500140,
+            8500160,
+            date_time("2025-03-03", "11:00"),
+            date_time("2025-03-03", "13:00"),
+        );
+        assert_eq!(1, itineraries.len());
+        assert_eq!(0, itineraries[0].transfer_count());
+    }
+}
@@ -0,0 +1,291 @@
+// ------------------------------------------------------------------------------------------------
+// --- Travel time matrix
+// ------------------------------------------------------------------------------------------------
+
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use bincode::config;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::DataStorage;
+
+impl DataStorage {
+    /// Returns the minimum travel time and the number of transfers between each pair of stops, for the departures
+    /// sampled every interval (at least 1 minute) between from and until on the date. The travel time of a sample
+    /// starts at the sampled departure time, so it includes the waiting time at the origin.
+    /// The origins are processed in parallel, on as many threads as available.
+    pub fn travel_time_matrix(
+        &self,
+        stop_ids: &[i32],
+        date: NaiveDate,
+        from: NaiveTime,
+        until: NaiveTime,
+        interval: TimeDelta,
+        max_transfers: usize,
+    ) -> TravelTimeMatrix {
+        let interval = interval.max(TimeDelta::minutes(1));
+        let mut departure_times = Vec::new();
+        let mut departure_at = NaiveDateTime::new(date, from);
+        while departure_at <= NaiveDateTime::new(date, until) {
+            departure_times.push(departure_at);
+            departure_at += interval;
+        }
+
+        let thread_count = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1)
+            .min(stop_ids.len().max(1));
+        let next_origin = AtomicUsize::new(0);
+
+        let mut rows: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..thread_count)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut rows = Vec::new();
+                        loop {
+                            let origin = next_origin.fetch_add(1, Ordering::Relaxed);
+                            if origin >= stop_ids.len() {
+                                break;
+                            }
+                            let row = self.travel_time_row(
+                                stop_ids[origin],
+                                stop_ids,
+                                &departure_times,
+                                max_transfers,
+                            );
+                            rows.push((origin, row));
+                        }
+                        rows
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                // unwrap: A panic in a worker thread is propagated.
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        rows.sort_unstable_by_key(|&(origin, _)| origin);
+
+        TravelTimeMatrix {
+            stop_ids: stop_ids.to_vec(),
+            cells: rows.into_iter().flat_map(|(_, row)| row).collect(),
+        }
+    }
+
+    fn travel_time_row(
+        &self,
+        origin_stop_id: i32,
+        stop_ids: &[i32],
+        departure_times: &[NaiveDateTime],
+        max_transfers: usize,
+    ) -> Vec<Option<(u32, u32)>> {
+        let mut row = vec![None; stop_ids.len()];
+
+        for &departure_at in departure_times {
+            let reachability = self.reachability(origin_stop_id, departure_at, max_transfers);
+
+            for (cell, &stop_id) in row.iter_mut().zip(stop_ids) {
+                let Some(&(arrival_time, transfer_count)) = reachability.arrivals().get(&stop_id)
+                else {
+                    continue;
+                };
+                let candidate = (
+                    (arrival_time - departure_at).num_minutes() as u32,
+                    transfer_count as u32,
+                );
+                // Ties on the travel time are broken by the number of transfers.
+                if cell.is_none_or(|current| candidate < current) {
+                    *cell = Some(candidate);
+                }
+            }
+        }
+        row
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- TravelTimeMatrix
+// ------------------------------------------------------------------------------------------------
+
+/// Origin-destination matrix of minimum travel times, in minutes, and numbers of transfers.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TravelTimeMatrix {
+    stop_ids: Vec<i32>,
+    cells: Vec<Option<(u32, u32)>>, // Row-major, origin => destination, (travel_time, transfer_count) or None if unreachable.
+}
+
+impl TravelTimeMatrix {
+    // Getters/Setters
+
+    pub fn stop_ids(&self) -> &Vec<i32> {
+        &self.stop_ids
+    }
+
+    // Functions
+
+    pub fn travel_time(&self, origin_stop_id: i32, destination_stop_id: i32) -> Option<TimeDelta> {
+        self.cell(origin_stop_id, destination_stop_id)
+            .map(|(travel_time, _)| TimeDelta::minutes(travel_time.into()))
+    }
+
+    pub fn transfer_count(&self, origin_stop_id: i32, destination_stop_id: i32) -> Option<usize> {
+        self.cell(origin_stop_id, destination_stop_id)
+            .map(|(_, transfer_count)| transfer_count as usize)
+    }
+
+    /// Writes one line per pair of stops: origin_stop_id,destination_stop_id,travel_time,transfer_count.
+    /// The travel time and the number of transfers are left empty if the destination is unreachable.
+    pub fn write_csv(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(
+            writer,
+            "origin_stop_id,destination_stop_id,travel_time,transfer_count"
+        )?;
+
+        let size = self.stop_ids.len();
+        for (index, cell) in self.cells.iter().enumerate() {
+            let origin_stop_id = self.stop_ids[index / size];
+            let destination_stop_id = self.stop_ids[index % size];
+            match cell {
+                Some((travel_time, transfer_count)) => writeln!(
+                    writer,
+                    "{origin_stop_id},{destination_stop_id},{travel_time},{transfer_count}"
+                )?,
+                None => writeln!(writer, "{origin_stop_id},{destination_stop_id},,")?,
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn write_binary(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let data = bincode::serde::encode_to_vec(self, config::standard())?;
+        fs::write(path, data)?;
+        Ok(())
+    }
+
+    pub fn load_binary(path: &str) -> Result<Self, Box<dyn Error>> {
+        let data = fs::read(path)?;
+        let (matrix, _) = bincode::serde::decode_from_slice(&data, config::standard())?;
+        Ok(matrix)
+    }
+
+    fn cell(&self, origin_stop_id: i32, destination_stop_id: i32) -> Option<(u32, u32)> {
+        let origin = self.stop_ids.iter().position(|&id| id == origin_stop_id)?;
+        let destination = self
+            .stop_ids
+            .iter()
+            .position(|&id| id == destination_stop_id)?;
+        self.cells[origin * self.stop_ids.len() + destination]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::tests::data_storage;
+    use pretty_assertions::assert_eq;
+
+    /// Matrix between A, B and D, for the departures between 07:50 and 08:00 on 03.03.2025.
+    fn travel_time_matrix(interval: TimeDelta) -> TravelTimeMatrix {
+        data_storage().travel_time_matrix(
+            &[8500010, 8500020, 8500040],
+            NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+            NaiveTime::from_hms_opt(7, 50, 0).unwrap(),
+            NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            interval,
+            2,
+        )
+    }
+
+    fn temp_path(extension: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "hrdf_parser_matrix_{}.{extension}",
+                std::process::id()
+            ))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn cells_v207() {
+        let matrix = travel_time_matrix(TimeDelta::minutes(5));
+
+        assert_eq!(&vec![8500010, 8500020, 8500040], matrix.stop_ids());
+        // The best sample leaves at 08:00, the travel time includes the waiting time at the origin.
+        assert_eq!(
+            Some(TimeDelta::minutes(30)),
+            matrix.travel_time(8500010, 8500020)
+        );
+        assert_eq!(Some(0), matrix.transfer_count(8500010, 8500020));
+        assert_eq!(
+            Some(TimeDelta::minutes(90)),
+            matrix.travel_time(8500010, 8500040)
+        );
+        assert_eq!(Some(1), matrix.transfer_count(8500010, 8500040));
+        assert_eq!(
+            Some(TimeDelta::minutes(90)),
+            matrix.travel_time(8500020, 8500040)
+        );
+        // Nothing goes back to A, nor leaves from D.
+        assert_eq!(None, matrix.travel_time(8500020, 8500010));
+        assert_eq!(None, matrix.travel_time(8500040, 8500020));
+        // Unknown stop.
+        assert_eq!(None, matrix.travel_time(8500010, 8500030));
+    }
+
+    #[test]
+    fn interval_of_at_least_1_minute_v207() {
+        let matrix = travel_time_matrix(TimeDelta::zero());
+
+        assert_eq!(
+            Some(TimeDelta::minutes(30)),
+            matrix.travel_time(8500010, 8500020)
+        );
+    }
+
+    #[test]
+    fn write_csv_v207() {
+        let matrix = travel_time_matrix(TimeDelta::minutes(5));
+        let path = temp_path("csv");
+
+        matrix.write_csv(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(10, lines.len());
+        assert_eq!(
+            "origin_stop_id,destination_stop_id,travel_time,transfer_count",
+            lines[0]
+        );
+        assert_eq!("8500010,8500020,30,0", lines[2]);
+        assert_eq!("8500010,8500040,90,1", lines[3]);
+        assert_eq!("8500020,8500010,,", lines[4]);
+    }
+
+    #[test]
+    fn binary_round_trip_v207() {
+        let matrix = travel_time_matrix(TimeDelta::minutes(5));
+        let path = temp_path("bin");
+
+        matrix.write_binary(&path).unwrap();
+        let loaded = TravelTimeMatrix::load_binary(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(matrix.stop_ids(), loaded.stop_ids());
+        assert_eq!(matrix.cells, loaded.cells);
+    }
+}
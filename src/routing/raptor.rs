@@ -0,0 +1,716 @@
+// ------------------------------------------------------------------------------------------------
+// --- Earliest arrival planner (RAPTOR)
+// ------------------------------------------------------------------------------------------------
+//
+// Round-based algorithm: the round k computes the earliest arrival at each stop with at most k rides.
+// See D. Delling, T. Pajor and R. F. Werneck, "Round-Based Public Transit Routing" (2012).
+// The trip patterns are the routes of the algorithm and the METABHF stop connections its footpaths.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    footpath::StopConnectionView,
+    models::{Journey, Model, TripPattern},
+    routing::{Itinerary, Leg, RideLeg, WalkLeg, minutes_of_day, walk_attribute_ids},
+    storage::DataStorage,
+    utils::{add_1_day, sub_1_day},
+};
+
+impl DataStorage {
+    /// Returns the itineraries from the departure stop to the arrival stop, leaving at departure_at at the earliest.
+    /// The itineraries are Pareto-optimal on the arrival time and the number of transfers:
+    /// one itinerary per number of transfers (up to max_transfers) which arrives earlier than with fewer transfers.
+    /// They are sorted by number of transfers.
+    ///
+    /// The minimum exchange times (see DataStorage::exchange_time), the stop connections (METABHF file)
+    /// and the through services (DURCHBI file) are taken into account.
+    /// The departure stop can be a meta stop (METABHF file), the itineraries then start from any of its stops.
+    pub fn plan_journeys(
+        &self,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        departure_at: NaiveDateTime,
+        max_transfers: usize,
+    ) -> Vec<Itinerary<'_>> {
+        let departure_stop_ids = self.resolve_stop_ids(departure_stop_id);
+        if departure_stop_ids.contains(&arrival_stop_id) {
+            return Vec::new();
+        }
+
+        let departure_time = minutes_of_day(departure_at.time());
+        let sources: Vec<_> = departure_stop_ids
+            .into_iter()
+            .map(|stop_id| (stop_id, departure_time))
+            .collect();
+
+        let mut raptor = Raptor::new(self, departure_at.date(), Some(arrival_stop_id));
+        raptor.run(&sources, max_transfers + 1);
+
+        (0..raptor.rounds.len())
+            .filter(|&round| raptor.rounds[round].contains_key(&arrival_stop_id))
+            .map(|round| raptor.itinerary(round, arrival_stop_id))
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Raptor
+// ------------------------------------------------------------------------------------------------
+
+/// A ride on a journey operating on a service date. The times are in minutes since the midnight of the query date.
+#[derive(Clone, Copy, Debug)]
+struct Ride {
+    journey_id: i32,
+    service_date: NaiveDate,
+    departure_index: usize,
+    arrival_index: usize,
+    departure_time: i32,
+    arrival_time: i32,
+    previous: Option<usize>, // The ride the passenger stays on board from (through service).
+}
+
+/// How a stop was reached in a round.
+#[derive(Clone, Copy, Debug)]
+enum Label {
+    Origin,
+    Ride(usize), // Index in Raptor::rides.
+    Walk {
+        departure_stop_id: i32,
+        departure_time: i32,
+        stop_connection_id: i32,
+        ride: Option<usize>, // The ride before the walk, None when walking from the origin.
+    },
+}
+
+/// A trip which can be boarded at a stop of a trip pattern.
+#[derive(Clone, Copy, Debug)]
+struct Boarding {
+    journey_index: usize, // Position of the journey in the trip pattern.
+    service_date: NaiveDate,
+    start: i32, // Departure at the first stop.
+    departure_index: usize,
+    departure_time: i32,
+}
+
+pub(super) struct Raptor<'a> {
+    data_storage: &'a DataStorage,
+    date: NaiveDate, // The times are in minutes since the midnight of this date.
+    target_stop_id: Option<i32>, // Used to prune the search.
+    rides: Vec<Ride>,
+    rounds: Vec<FxHashMap<i32, (i32, Label)>>, // For each round, stop_id => (arrival time, label) of the stops improved in that round.
+    best_arrivals: FxHashMap<i32, i32>,
+    operating_journeys: FxHashMap<(i32, NaiveDate), bool>,
+    boardings: FxHashMap<(i32, usize), Vec<Boarding>>, // (trip_pattern_id, index of the stop) => trips which can be boarded there.
+}
+
+impl<'a> Raptor<'a> {
+    pub(super) fn new(
+        data_storage: &'a DataStorage,
+        date: NaiveDate,
+        target_stop_id: Option<i32>,
+    ) -> Self {
+        Self {
+            data_storage,
+            date,
+            target_stop_id,
+            rides: Vec::new(),
+            rounds: Vec::new(),
+            best_arrivals: FxHashMap::default(),
+            operating_journeys: FxHashMap::default(),
+            boardings: FxHashMap::default(),
+        }
+    }
+
+    /// Runs the search from the sources, (stop_id, departure time) pairs.
+    pub(super) fn run(&mut self, sources: &[(i32, i32)], max_rides: usize) {
+        let mut round = FxHashMap::default();
+        for &(stop_id, departure_time) in sources {
+            if self.is_improvement(stop_id, departure_time) {
+                round.insert(stop_id, (departure_time, Label::Origin));
+                self.best_arrivals.insert(stop_id, departure_time);
+            }
+        }
+        let mut source_stop_ids: Vec<_> = round.keys().copied().collect();
+        source_stop_ids.sort_unstable();
+        self.rounds.push(round);
+        let mut marked_stop_ids = self.relax_stop_connections(0, source_stop_ids);
+
+        for k in 1..=max_rides {
+            self.rounds.push(FxHashMap::default());
+
+            // The patterns to scan, from the first marked stop.
+            let mut trip_patterns = FxHashMap::default();
+            for stop_id in &marked_stop_ids {
+                let entries = self.data_storage.trip_patterns_by_stop_id().get(stop_id);
+                for &(trip_pattern_id, index) in entries.into_iter().flatten() {
+                    let start_index = trip_patterns.entry(trip_pattern_id).or_insert(index);
+                    *start_index = (*start_index).min(index);
+                }
+            }
+            let mut trip_patterns: Vec<_> = trip_patterns.into_iter().collect();
+            trip_patterns.sort_unstable();
+
+            let mut improved_stop_ids = FxHashSet::default();
+            for (trip_pattern_id, start_index) in trip_patterns {
+                self.scan_trip_pattern(k, trip_pattern_id, start_index, &mut improved_stop_ids);
+            }
+            self.follow_through_services(k, &mut improved_stop_ids);
+
+            let mut improved_stop_ids: Vec<_> = improved_stop_ids.into_iter().collect();
+            improved_stop_ids.sort_unstable();
+            marked_stop_ids = self.relax_stop_connections(k, improved_stop_ids);
+
+            if marked_stop_ids.is_empty() {
+                break;
+            }
+        }
+    }
+
+    fn scan_trip_pattern(
+        &mut self,
+        k: usize,
+        trip_pattern_id: i32,
+        start_index: usize,
+        improved_stop_ids: &mut FxHashSet<i32>,
+    ) {
+        let data_storage = self.data_storage;
+        // unwrap: The trip patterns of the index always exist.
+        let trip_pattern = data_storage.trip_patterns().find(trip_pattern_id).unwrap();
+        let mut current: Option<Boarding> = None;
+
+        for (i, &stop_id) in trip_pattern.stop_ids().iter().enumerate().skip(start_index) {
+            if let Some(boarding) = current {
+                let journey_id = trip_pattern.journey_ids()[boarding.journey_index];
+                let journey = self.journey(journey_id);
+                let arrival_offset = trip_pattern.offsets()[boarding.journey_index][i].0;
+
+                if let Some(arrival_offset) = arrival_offset
+                    && journey.route()[i].alighting_allowed()
+                    && journey.serves_stop_on(stop_id, boarding.service_date, data_storage)
+                {
+                    let ride = Ride {
+                        journey_id,
+                        service_date: boarding.service_date,
+                        departure_index: boarding.departure_index,
+                        arrival_index: i,
+                        departure_time: boarding.departure_time,
+                        arrival_time: boarding.start + arrival_offset,
+                        previous: None,
+                    };
+                    if self.improve(k, stop_id, ride) {
+                        improved_stop_ids.insert(stop_id);
+                    }
+                }
+            }
+
+            let Some(&(ready_time, label)) = self.rounds[k - 1].get(&stop_id) else {
+                continue;
+            };
+            if current.is_some_and(|boarding| {
+                let departure_offset = trip_pattern.offsets()[boarding.journey_index][i].1;
+                departure_offset.is_some_and(|offset| boarding.start + offset <= ready_time)
+            }) {
+                // No earlier trip can be caught.
+                continue;
+            }
+
+            if let Some(boarding) = self.earliest_boarding(trip_pattern, i, ready_time, label) {
+                let is_earlier = current.is_none_or(|current| {
+                    let departure_offset = trip_pattern.offsets()[current.journey_index][i].1;
+                    departure_offset
+                        .is_none_or(|offset| boarding.departure_time < current.start + offset)
+                });
+                if is_earlier {
+                    current = Some(boarding);
+                }
+            }
+        }
+    }
+
+    /// Returns the first trip of the pattern which can be boarded at the stop, the passenger being there at ready_time.
+    fn earliest_boarding(
+        &mut self,
+        trip_pattern: &TripPattern,
+        index: usize,
+        ready_time: i32,
+        label: Label,
+    ) -> Option<Boarding> {
+        let data_storage = self.data_storage;
+        let previous_ride = match label {
+            Label::Ride(ride) => Some(self.rides[ride]),
+            _ => None,
+        };
+
+        let key = (trip_pattern.id(), index);
+        if !self.boardings.contains_key(&key) {
+            let boardings = self.create_boardings(trip_pattern, index);
+            self.boardings.insert(key, boardings);
+        }
+        let boardings = &self.boardings[&key];
+        let first = boardings.partition_point(|boarding| boarding.departure_time < ready_time);
+
+        boardings[first..].iter().copied().find(|boarding| {
+            let journey_id = trip_pattern.journey_ids()[boarding.journey_index];
+
+            previous_ride.is_none_or(|previous_ride| {
+                if previous_ride.journey_id == journey_id
+                    && previous_ride.service_date == boarding.service_date
+                {
+                    // The passenger already was on this trip.
+                    return false;
+                }
+                let (exchange_time, _) = data_storage.exchange_time(
+                    self.journey(previous_ride.journey_id),
+                    previous_ride.arrival_index,
+                    self.journey(journey_id),
+                    boarding.departure_index,
+                    previous_ride.service_date,
+                );
+                boarding.departure_time >= ready_time + i32::from(exchange_time)
+            })
+        })
+    }
+
+    /// Returns the trips of the pattern which can be boarded at the stop, on the service dates around the query date,
+    /// sorted by departure time.
+    fn create_boardings(&mut self, trip_pattern: &TripPattern, index: usize) -> Vec<Boarding> {
+        let data_storage = self.data_storage;
+        let stop_id = trip_pattern.stop_ids()[index];
+        let mut boardings = Vec::new();
+
+        // The trips of the previous service day can depart after midnight.
+        for service_date in [sub_1_day(self.date), self.date, add_1_day(self.date)] {
+            for (journey_index, &journey_id) in trip_pattern.journey_ids().iter().enumerate() {
+                let Some(departure_offset) = trip_pattern.offsets()[journey_index][index].1 else {
+                    continue;
+                };
+                let journey = self.journey(journey_id);
+                if !journey.route()[index].boarding_allowed()
+                    || !self.operates_on(journey_id, service_date)
+                    || !journey.serves_stop_on(stop_id, service_date, data_storage)
+                {
+                    continue;
+                }
+
+                let start = self.start_of(journey, service_date);
+                boardings.push(Boarding {
+                    journey_index,
+                    service_date,
+                    start,
+                    departure_index: index,
+                    departure_time: start + departure_offset,
+                });
+            }
+        }
+
+        boardings.sort_by_key(|boarding| (boarding.departure_time, boarding.journey_index));
+        boardings
+    }
+
+    /// The passengers of the rides ending where a through service starts can stay on board.
+    fn follow_through_services(&mut self, k: usize, improved_stop_ids: &mut FxHashSet<i32>) {
+        let data_storage = self.data_storage;
+        let mut stop_ids: Vec<_> = improved_stop_ids.iter().copied().collect();
+        stop_ids.sort_unstable();
+
+        while let Some(stop_id) = stop_ids.pop() {
+            let (_, Label::Ride(ride_index)) = self.rounds[k][&stop_id] else {
+                continue;
+            };
+            let ride = self.rides[ride_index];
+            let journey = self.journey(ride.journey_id);
+
+            for through_journey in data_storage.through_journeys(journey, ride.service_date) {
+                if through_journey.from_stop_id() != stop_id {
+                    continue;
+                }
+
+                let (journey, service_date) =
+                    (through_journey.journey(), through_journey.service_date());
+                let departure_index = through_journey.departure_index();
+                let offsets = self.offsets(journey.id());
+                let Some(departure_offset) = offsets[departure_index].1 else {
+                    continue;
+                };
+                let start = self.start_of(journey, service_date);
+
+                for (i, route_entry) in journey.route().iter().enumerate().skip(departure_index + 1)
+                {
+                    let Some(arrival_offset) = offsets[i].0 else {
+                        continue;
+                    };
+                    if !route_entry.alighting_allowed()
+                        || !journey.serves_stop_on(
+                            route_entry.stop_id(),
+                            service_date,
+                            data_storage,
+                        )
+                    {
+                        continue;
+                    }
+
+                    let next_ride = Ride {
+                        journey_id: journey.id(),
+                        service_date,
+                        departure_index,
+                        arrival_index: i,
+                        departure_time: start + departure_offset,
+                        arrival_time: start + arrival_offset,
+                        previous: Some(ride_index),
+                    };
+                    if self.improve(k, route_entry.stop_id(), next_ride) {
+                        improved_stop_ids.insert(route_entry.stop_id());
+                        stop_ids.push(route_entry.stop_id());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Walks from the given stops through the stop connections. Returns the stops improved in the round.
+    fn relax_stop_connections(&mut self, k: usize, stop_ids: Vec<i32>) -> Vec<i32> {
+        let data_storage = self.data_storage;
+        let mut marked_stop_ids = stop_ids.clone();
+
+        for stop_id in stop_ids {
+            let (departure_time, ride) = match self.rounds[k][&stop_id] {
+                (departure_time, Label::Ride(ride)) => (departure_time, Some(ride)),
+                (departure_time, Label::Origin) => (departure_time, None),
+                (_, Label::Walk { .. }) => continue,
+            };
+
+            for footpath in data_storage.footpaths(stop_id, StopConnectionView::Directed) {
+                let arrival_stop_id = footpath.arrival_stop_id();
+                let arrival_time = departure_time + i32::from(footpath.duration());
+                let label = Label::Walk {
+                    departure_stop_id: stop_id,
+                    departure_time,
+                    stop_connection_id: footpath.stop_connection_ids()[0],
+                    ride,
+                };
+
+                if self.is_improvement(arrival_stop_id, arrival_time) {
+                    self.rounds[k].insert(arrival_stop_id, (arrival_time, label));
+                    self.best_arrivals.insert(arrival_stop_id, arrival_time);
+                    if !marked_stop_ids.contains(&arrival_stop_id) {
+                        marked_stop_ids.push(arrival_stop_id);
+                    }
+                }
+            }
+        }
+
+        marked_stop_ids
+    }
+
+    /// Records the ride if it reaches the stop earlier than known so far.
+    fn improve(&mut self, k: usize, stop_id: i32, ride: Ride) -> bool {
+        if !self.is_improvement(stop_id, ride.arrival_time) {
+            return false;
+        }
+
+        self.rides.push(ride);
+        let label = Label::Ride(self.rides.len() - 1);
+        self.rounds[k].insert(stop_id, (ride.arrival_time, label));
+        self.best_arrivals.insert(stop_id, ride.arrival_time);
+        true
+    }
+
+    fn is_improvement(&self, stop_id: i32, arrival_time: i32) -> bool {
+        let is_best = |stop_id| {
+            self.best_arrivals
+                .get(&stop_id)
+                .is_none_or(|&best_arrival| arrival_time < best_arrival)
+        };
+        is_best(stop_id) && self.target_stop_id.is_none_or(is_best)
+    }
+
+    /// Returns the earliest arrival at each reached stop and the number of rides needed.
+    pub(super) fn earliest_arrivals(&self) -> FxHashMap<i32, (NaiveDateTime, usize)> {
+        let mut earliest_arrivals = FxHashMap::default();
+        // The arrivals only improve from a round to the next.
+        for (k, round) in self.rounds.iter().enumerate() {
+            for (&stop_id, &(arrival_time, _)) in round {
+                earliest_arrivals.insert(stop_id, (self.to_date_time(arrival_time), k));
+            }
+        }
+        earliest_arrivals
+    }
+
+    /// Builds the itinerary reaching the stop in the round.
+    fn itinerary(&self, k: usize, stop_id: i32) -> Itinerary<'a> {
+        let mut legs = Vec::new();
+        let (mut k, mut stop_id) = (k, stop_id);
+
+        loop {
+            let (arrival_time, label) = self.rounds[k][&stop_id];
+            let ride = match label {
+                Label::Origin => break,
+                Label::Ride(ride) => ride,
+                Label::Walk {
+                    departure_stop_id,
+                    departure_time,
+                    stop_connection_id,
+                    ride,
+                } => {
+                    legs.push(Leg::Walk(WalkLeg::new(
+                        departure_stop_id,
+                        stop_id,
+                        self.to_date_time(departure_time),
+                        self.to_date_time(arrival_time),
+                        walk_attribute_ids(self.data_storage, stop_connection_id),
+                    )));
+                    match ride {
+                        Some(ride) => ride,
+                        None => break,
+                    }
+                }
+            };
+
+            stop_id = self.push_ride_legs(ride, &mut legs);
+            k -= 1;
+        }
+
+        legs.reverse();
+        Itinerary::new(legs)
+    }
+
+    /// Adds the legs of the ride and of the rides the passenger stayed on board from.
+    /// Returns the stop where the passenger boarded.
+    fn push_ride_legs(&self, ride_index: usize, legs: &mut Vec<Leg<'a>>) -> i32 {
+        let data_storage = self.data_storage;
+        let mut ride_index = ride_index;
+
+        loop {
+            let ride = self.rides[ride_index];
+            let journey = self.journey(ride.journey_id);
+
+            legs.push(Leg::Ride(RideLeg::new(
+                journey,
+                ride.service_date,
+                ride.departure_index,
+                ride.arrival_index,
+                self.to_date_time(ride.departure_time),
+                self.to_date_time(ride.arrival_time),
+                journey.platform_at(ride.departure_index, ride.service_date, data_storage),
+                journey.platform_at(ride.arrival_index, ride.service_date, data_storage),
+                ride.previous.is_some(),
+            )));
+
+            match ride.previous {
+                Some(previous) => ride_index = previous,
+                None => return journey.route()[ride.departure_index].stop_id(),
+            }
+        }
+    }
+
+    fn journey(&self, journey_id: i32) -> &'a Journey {
+        // unwrap: The journeys of the indexes always exist.
+        self.data_storage.journeys().find(journey_id).unwrap()
+    }
+
+    fn offsets(&self, journey_id: i32) -> &'a Vec<(Option<i32>, Option<i32>)> {
+        let data_storage = self.data_storage;
+        // unwrap: Every journey is part of a trip pattern.
+        let trip_pattern_id = data_storage.trip_pattern_by_journey_id()[&journey_id];
+        let trip_pattern = data_storage.trip_patterns().find(trip_pattern_id).unwrap();
+        trip_pattern.offsets_of(journey_id).unwrap()
+    }
+
+    fn operates_on(&mut self, journey_id: i32, service_date: NaiveDate) -> bool {
+        let data_storage = self.data_storage;
+        *self
+            .operating_journeys
+            .entry((journey_id, service_date))
+            .or_insert_with(|| {
+                // unwrap: The journeys of the indexes always exist.
+                let journey = data_storage.journeys().find(journey_id).unwrap();
+                journey.operates_on(service_date, data_storage)
+            })
+    }
+
+    /// Departure of the journey at its first stop on the service date.
+    fn start_of(&self, journey: &Journey, service_date: NaiveDate) -> i32 {
+        let days = (service_date - self.date).num_days() as i32;
+        // unwrap: The first route entry always has a departure time.
+        days * 24 * 60 + minutes_of_day(journey.route()[0].departure_time().unwrap())
+    }
+
+    fn to_date_time(&self, minutes: i32) -> NaiveDateTime {
+        NaiveDateTime::new(self.date, NaiveTime::MIN) + TimeDelta::minutes(minutes.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::tests::{data_storage, date_time, describe};
+    use pretty_assertions::assert_eq;
+
+    fn plan(
+        data_storage: &DataStorage,
+        departure_stop_id: i32,
+        arrival_stop_id: i32,
+        departure_at: NaiveDateTime,
+        max_transfers: usize,
+    ) -> Vec<Vec<String>> {
+        data_storage
+            .plan_journeys(
+                departure_stop_id,
+                arrival_stop_id,
+                departure_at,
+                max_transfers,
+            )
+            .iter()
+            .map(describe)
+            .collect()
+    }
+
+    #[test]
+    fn direct_ride_v207() {
+        let data_storage = data_storage();
+        let departure_at = date_time("2025-03-03", "07:50");
+
+        assert_eq!(
+            vec![vec!["ride 8500010 08:00 8500020 08:30"]],
+            plan(&data_storage, 8500010, 8500020, departure_at, 2)
+        );
+        // Too late for the journey of the day, the one of the next day is taken.
+        let departure_at = date_time("2025-03-03", "08:01");
+        let itineraries = data_storage.plan_journeys(8500010, 8500020, departure_at, 2);
+        assert_eq!(1, itineraries.len());
+        assert_eq!(
+            Some(date_time("2025-03-04", "08:00")),
+            itineraries[0].departure_time()
+        );
+    }
+
+    #[test]
+    fn one_transfer_v207() {
+        let data_storage = data_storage();
+        let departure_at = date_time("2025-03-03", "07:50");
+
+        assert!(plan(&data_storage, 8500010, 8500040, departure_at, 0).is_empty());
+        let itineraries = data_storage.plan_journeys(8500010, 8500040, departure_at, 1);
+        assert_eq!(1, itineraries.len());
+        assert_eq!(1, itineraries[0].transfer_count());
+        assert_eq!(
+            vec![
+                "ride 8500010 08:00 8500030 09:00",
+                "ride 8500030 09:10 8500040 09:30",
+            ],
+            describe(&itineraries[0])
+        );
+    }
+
+    #[test]
+    fn pareto_optimal_itineraries_v207() {
+        let data_storage = data_storage();
+        let departure_at = date_time("2025-03-03", "07:50");
+
+        // Changing at B arrives earlier at C.
+        assert_eq!(
+            vec![
+                vec!["ride 8500010 08:00 8500030 09:00"],
+                vec![
+                    "ride 8500010 08:00 8500020 08:30",
+                    "ride 8500020 08:35 8500030 08:50",
+                ],
+            ],
+            plan(&data_storage, 8500010, 8500030, departure_at, 3)
+        );
+        // But not at D, the itinerary with 2 transfers is pruned.
+        assert_eq!(
+            1,
+            plan(&data_storage, 8500010, 8500040, departure_at, 3).len()
+        );
+    }
+
+    #[test]
+    fn footpath_transfer_v207() {
+        let data_storage = data_storage();
+        let departure_at = date_time("2025-03-03", "07:50");
+
+        let itineraries = data_storage.plan_journeys(8500010, 8500070, departure_at, 1);
+        assert_eq!(1, itineraries.len());
+        assert_eq!(1, itineraries[0].transfer_count());
+        assert_eq!(
+            vec![
+                "ride 8500010 08:00 8500020 08:30",
+                "walk 8500020 08:30 8500060 08:32",
+                "ride 8500060 08:40 8500070 09:00",
+            ],
+            describe(&itineraries[0])
+        );
+    }
+
+    #[test]
+    fn previous_service_day_after_midnight_v207() {
+        let data_storage = data_storage();
+
+        // The journey 5 only operates on 03.03.2025, it reaches I after midnight.
+        let itineraries =
+            data_storage.plan_journeys(8500090, 8500100, date_time("2025-03-04", "00:05"), 0);
+        assert_eq!(1, itineraries.len());
+        let Leg::Ride(ride) = &itineraries[0].legs()[0] else {
+            panic!("The leg must be a ride.");
+        };
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+            ride.service_date()
+        );
+        assert_eq!(date_time("2025-03-04", "00:12"), ride.departure_time());
+        assert_eq!(date_time("2025-03-04", "00:40"), ride.arrival_time());
+
+        let itineraries =
+            data_storage.plan_journeys(8500080, 8500100, date_time("2025-03-03", "23:00"), 0);
+        assert_eq!(
+            Some(date_time("2025-03-04", "00:40")),
+            itineraries[0].arrival_time()
+        );
+
+        // Not on the other days.
+        assert!(
+            plan(
+                &data_storage,
+                8500090,
+                8500100,
+                date_time("2025-03-05", "00:05"),
+                0
+            )
+            .is_empty()
+        );
+        assert!(
+            plan(
+                &data_storage,
+                8500080,
+                8500100,
+                date_time("2025-03-04", "23:00"),
+                0
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn through_service_v207() {
+        let data_storage = data_storage();
+        let departure_at = date_time("2025-03-03", "11:50");
+
+        // The passengers stay on board at O, no transfer is needed.
+        let itineraries = data_storage.plan_journeys(8500140, 8500160, departure_at, 0);
+        assert_eq!(1, itineraries.len());
+        assert_eq!(0, itineraries[0].transfer_count());
+        assert_eq!(
+            vec![
+                "ride 8500140 12:00 8500150 12:30",
+                "ride 8500150 12:31 8500160 13:00",
+            ],
+            describe(&itineraries[0])
+        );
+        let Leg::Ride(ride_leg) = &itineraries[0].legs()[1] else {
+            panic!("The second leg must be a ride.");
+        };
+        assert!(ride_leg.is_through_service());
+    }
+}
@@ -0,0 +1,385 @@
+// ------------------------------------------------------------------------------------------------
+// --- Reachability (isochrones)
+// ------------------------------------------------------------------------------------------------
+
+use chrono::{NaiveDateTime, TimeDelta};
+use rustc_hash::FxHashMap;
+use serde_json::{Value, json};
+
+use crate::{
+    models::{CoordinateSystem, Coordinates, Model},
+    routing::{minutes_of_day, raptor::Raptor},
+    storage::DataStorage,
+};
+
+/// Walking speed used to reach the stops from coordinates, in meters per minute (4.5 km/h).
+const WALKING_SPEED: f64 = 75.0;
+
+impl DataStorage {
+    /// Returns the earliest arrival at every stop which can be reached from the departure stop,
+    /// leaving at departure_at at the earliest, with at most max_transfers transfers.
+    /// The rules of DataStorage::plan_journeys apply, the departure stop can be a meta stop.
+    pub fn reachability(
+        &self,
+        departure_stop_id: i32,
+        departure_at: NaiveDateTime,
+        max_transfers: usize,
+    ) -> Reachability {
+        let departure_time = minutes_of_day(departure_at.time());
+        let sources: Vec<_> = self
+            .resolve_stop_ids(departure_stop_id)
+            .into_iter()
+            .map(|stop_id| (stop_id, departure_time))
+            .collect();
+        create_reachability(self, &sources, departure_at, max_transfers)
+    }
+
+    /// Same as DataStorage::reachability, from a location. The stops are reached on foot in a straight line,
+    /// within max_walking_duration.
+    pub fn reachability_from_coordinates(
+        &self,
+        coordinates: Coordinates,
+        departure_at: NaiveDateTime,
+        max_walking_duration: TimeDelta,
+        max_transfers: usize,
+    ) -> Reachability {
+        let departure_time = minutes_of_day(departure_at.time());
+        let max_distance = max_walking_duration.num_minutes() as f64 * WALKING_SPEED;
+
+        let mut sources: Vec<_> = self
+            .stops()
+            .entries()
+            .into_iter()
+            .filter_map(|stop| {
+                let stop_coordinates = match coordinates.coordinate_system() {
+                    CoordinateSystem::LV95 => stop.lv95_coordinates(),
+                    CoordinateSystem::WGS84 => stop.wgs84_coordinates(),
+                }?;
+                let distance = coordinates.distance(&stop_coordinates)?;
+                if distance > max_distance {
+                    return None;
+                }
+                let walking_duration = (distance / WALKING_SPEED).ceil() as i32;
+                Some((stop.id(), departure_time + walking_duration))
+            })
+            .collect();
+        sources.sort_unstable();
+
+        create_reachability(self, &sources, departure_at, max_transfers)
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Reachability
+// ------------------------------------------------------------------------------------------------
+
+/// Earliest arrivals at the stops reached from a departure point.
+#[derive(Debug)]
+pub struct Reachability {
+    departure_at: NaiveDateTime,
+    arrivals: FxHashMap<i32, (NaiveDateTime, usize)>, // stop_id => (earliest arrival, number of transfers)
+}
+
+impl Reachability {
+    // Getters/Setters
+
+    pub fn departure_at(&self) -> NaiveDateTime {
+        self.departure_at
+    }
+
+    pub fn arrivals(&self) -> &FxHashMap<i32, (NaiveDateTime, usize)> {
+        &self.arrivals
+    }
+
+    // Functions
+
+    pub fn arrival_time(&self, stop_id: i32) -> Option<NaiveDateTime> {
+        self.arrivals
+            .get(&stop_id)
+            .map(|&(arrival_time, _)| arrival_time)
+    }
+
+    pub fn travel_time(&self, stop_id: i32) -> Option<TimeDelta> {
+        self.arrival_time(stop_id)
+            .map(|arrival_time| arrival_time - self.departure_at)
+    }
+
+    /// Number of transfers of the earliest arrival.
+    pub fn transfer_count(&self, stop_id: i32) -> Option<usize> {
+        self.arrivals
+            .get(&stop_id)
+            .map(|&(_, transfer_count)| transfer_count)
+    }
+
+    /// Returns the stops reached within the duration, sorted by id.
+    pub fn stop_ids_within(&self, duration: TimeDelta) -> Vec<i32> {
+        let mut stop_ids: Vec<_> = self
+            .arrivals
+            .iter()
+            .filter(|&(_, &(arrival_time, _))| arrival_time - self.departure_at <= duration)
+            .map(|(&stop_id, _)| stop_id)
+            .collect();
+        stop_ids.sort_unstable();
+        stop_ids
+    }
+
+    /// Returns a GeoJSON FeatureCollection of the stops reached within the largest band, as WGS84 points.
+    /// Each point has the properties stop_id, name, travel_time (in minutes) and band (the smallest band
+    /// containing the travel time, in minutes). The stops without WGS84 coordinates are left out.
+    pub fn to_geojson(&self, data_storage: &DataStorage, bands: &[TimeDelta]) -> Value {
+        let mut bands = bands.to_vec();
+        bands.sort_unstable();
+
+        let mut stop_ids: Vec<_> = self.arrivals.keys().copied().collect();
+        stop_ids.sort_unstable();
+
+        let features: Vec<_> = stop_ids
+            .into_iter()
+            .filter_map(|stop_id| {
+                let stop = data_storage.stops().find(stop_id)?;
+                let coordinates = stop.wgs84_coordinates()?;
+                let (latitude, longitude) = (coordinates.latitude()?, coordinates.longitude()?);
+                // unwrap: The stop has been reached.
+                let travel_time = self.travel_time(stop_id).unwrap();
+                let band = bands.iter().find(|&&band| travel_time <= band)?;
+
+                Some(json!({
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Point",
+                        "coordinates": [longitude, latitude],
+                    },
+                    "properties": {
+                        "stop_id": stop_id,
+                        "name": stop.name(),
+                        "travel_time": travel_time.num_minutes(),
+                        "band": band.num_minutes(),
+                    },
+                }))
+            })
+            .collect();
+
+        json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+fn create_reachability(
+    data_storage: &DataStorage,
+    sources: &[(i32, i32)],
+    departure_at: NaiveDateTime,
+    max_transfers: usize,
+) -> Reachability {
+    let mut raptor = Raptor::new(data_storage, departure_at.date(), None);
+    raptor.run(sources, max_transfers + 1);
+
+    let arrivals = raptor
+        .earliest_arrivals()
+        .into_iter()
+        .map(|(stop_id, (arrival_time, rides))| (stop_id, (arrival_time, rides.saturating_sub(1))))
+        .collect();
+
+    Reachability {
+        departure_at,
+        arrivals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routing::tests::date_time, storage::tests::data_storage_from};
+    use pretty_assertions::assert_eq;
+
+    /// - B 1: A 08:00, B 08:20/08:21, C 08:40
+    /// - B 2: C 08:50, D 09:30
+    /// - Footpath from B to E, 5 minutes. E has no coordinates.
+    /// - A and B are 500 m apart (LV95), A to D are 0.1° of latitude apart from each other (WGS84).
+    fn reachability_fixture() -> DataStorage {
+        data_storage_from(&[
+            (
+                "BAHNHOF",
+                &[
+                    "8500010     A$<1>",
+                    "8500020     B$<1>",
+                    "8500030     C$<1>",
+                    "8500040     D$<1>",
+                    "8500050     E$<1>",
+                ],
+            ),
+            (
+                "BFKOORD_LV95",
+                &[
+                    "8500010 2600000.000 1200000.000       0",
+                    "8500020 2600500.000 1200000.000       0",
+                ],
+            ),
+            (
+                "BFKOORD_WGS",
+                &[
+                    "8500010    7.000000   47.000000       0",
+                    "8500020    7.000000   47.100000       0",
+                    "8500030    7.000000   47.200000       0",
+                    "8500040    7.000000   47.300000       0",
+                ],
+            ),
+            ("METABHF", &["8500020 8500050 005"]),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G B   8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000000                               %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00820  00821                 %",
+                    "8500030 C                     00840                        %",
+                    "*Z 000002 000011                                           %",
+                    "*G B   8500030 8500040                                     %",
+                    "*A VE 8500030 8500040 000000                               %",
+                    "8500030 C                            00850                 %",
+                    "8500040 D                     00930                        %",
+                ],
+            ),
+        ])
+    }
+
+    /// Describes each reached stop as (stop_id, travel time in minutes, number of transfers), sorted by stop.
+    fn describe(reachability: &Reachability) -> Vec<(i32, i64, usize)> {
+        let mut arrivals: Vec<_> = reachability
+            .arrivals()
+            .keys()
+            .map(|&stop_id| {
+                (
+                    stop_id,
+                    reachability.travel_time(stop_id).unwrap().num_minutes(),
+                    reachability.transfer_count(stop_id).unwrap(),
+                )
+            })
+            .collect();
+        arrivals.sort_unstable();
+        arrivals
+    }
+
+    #[test]
+    fn reachability_v207() {
+        let data_storage = reachability_fixture();
+        let departure_at = date_time("2025-03-03", "07:50");
+
+        let reachability = data_storage.reachability(8500010, departure_at, 1);
+        assert_eq!(
+            vec![
+                (8500010, 0, 0),
+                (8500020, 30, 0),
+                (8500030, 50, 0),
+                (8500040, 100, 1),
+                (8500050, 35, 0),
+            ],
+            describe(&reachability)
+        );
+        assert_eq!(
+            Some(date_time("2025-03-03", "09:30")),
+            reachability.arrival_time(8500040)
+        );
+
+        // D needs a transfer.
+        let reachability = data_storage.reachability(8500010, departure_at, 0);
+        assert_eq!(None, reachability.arrival_time(8500040));
+    }
+
+    #[test]
+    fn stop_ids_within_v207() {
+        let data_storage = reachability_fixture();
+        let reachability = data_storage.reachability(8500010, date_time("2025-03-03", "07:50"), 1);
+
+        assert_eq!(
+            vec![8500010, 8500020],
+            reachability.stop_ids_within(TimeDelta::minutes(30))
+        );
+        assert_eq!(
+            vec![8500010, 8500020, 8500030, 8500050],
+            reachability.stop_ids_within(TimeDelta::minutes(60))
+        );
+    }
+
+    #[test]
+    fn to_geojson_v207() {
+        let data_storage = reachability_fixture();
+        let reachability = data_storage.reachability(8500010, date_time("2025-03-03", "07:50"), 1);
+
+        // The bands are sorted, D is beyond the largest one and E has no coordinates.
+        let geojson = reachability.to_geojson(
+            &data_storage,
+            &[TimeDelta::minutes(60), TimeDelta::minutes(30)],
+        );
+        assert_eq!("FeatureCollection", geojson["type"]);
+        let features = geojson["features"].as_array().unwrap();
+        let properties: Vec<_> = features
+            .iter()
+            .map(|feature| {
+                (
+                    feature["properties"]["stop_id"].as_i64().unwrap(),
+                    feature["properties"]["travel_time"].as_i64().unwrap(),
+                    feature["properties"]["band"].as_i64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![(8500010, 0, 30), (8500020, 30, 30), (8500030, 50, 60)],
+            properties
+        );
+        assert_eq!(
+            json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": [7.0, 47.1],
+                },
+                "properties": {
+                    "stop_id": 8500020,
+                    "name": "B",
+                    "travel_time": 30,
+                    "band": 30,
+                },
+            }),
+            features[1]
+        );
+    }
+
+    #[test]
+    fn reachability_from_coordinates_v207() {
+        let data_storage = reachability_fixture();
+        let departure_at = date_time("2025-03-03", "07:50");
+        // 250 m from A and B: 4 minutes on foot.
+        let coordinates = Coordinates::new(CoordinateSystem::LV95, 2600250.0, 1200000.0);
+
+        let reachability = data_storage.reachability_from_coordinates(
+            coordinates,
+            departure_at,
+            TimeDelta::minutes(5),
+            1,
+        );
+        assert_eq!(
+            Some(date_time("2025-03-03", "07:54")),
+            reachability.arrival_time(8500020)
+        );
+        assert_eq!(
+            Some(date_time("2025-03-03", "08:40")),
+            reachability.arrival_time(8500030)
+        );
+
+        // The stops are too far away.
+        let reachability = data_storage.reachability_from_coordinates(
+            coordinates,
+            departure_at,
+            TimeDelta::minutes(3),
+            1,
+        );
+        assert!(reachability.arrivals().is_empty());
+    }
+}
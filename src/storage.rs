@@ -1,19 +1,21 @@
 use std::error::Error;
 
-use chrono::{Days, NaiveDate};
+use chrono::NaiveDate;
 use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     JourneyId,
     models::{
-        Attribute, BitField, Direction, ExchangeTimeAdministration, ExchangeTimeJourney,
-        ExchangeTimeLine, Holiday, InformationText, Journey, JourneyPlatform, Line, Model,
-        Platform, Stop, StopConnection, ThroughService, TimetableMetadataEntry, TransportCompany,
-        TransportType, Version,
+        Attribute, BitField, BitFieldsByDay, BorderPoint, DayType, Direction,
+        ExchangeTimeAdministration, ExchangeTimeJourney, ExchangeTimeLine, Holiday,
+        InformationText, Journey, JourneyMetadataType, JourneyPlatform, JourneyRouteEntry, Line,
+        Model, Platform, Stop, StopConnection, StopGroup, ThroughService, TimetableInfo,
+        TimetableMetadataEntry, TimetablePeriod, TransportCompany, TransportType, TripPattern,
+        Version,
     },
     parsing,
-    utils::{count_days_between_two_dates, timetable_end_date, timetable_start_date},
+    utils::{count_days_between_two_dates, sub_1_day, timetable_info},
 };
 
 // ------------------------------------------------------------------------------------------------
@@ -26,6 +28,7 @@ pub struct DataStorage {
     bit_fields: ResourceStorage<BitField>,
     holidays: ResourceStorage<Holiday>,
     timetable_metadata: ResourceStorage<TimetableMetadataEntry>,
+    timetable_info: TimetableInfo,
 
     // Basic data.
     attributes: ResourceStorage<Attribute>,
@@ -38,12 +41,15 @@ pub struct DataStorage {
     // Stop data
     stops: ResourceStorage<Stop>,
     stop_connections: ResourceStorage<StopConnection>,
+    stop_groups: ResourceStorage<StopGroup>,
+    border_points: ResourceStorage<BorderPoint>,
 
     // Timetable data
     journeys: ResourceStorage<Journey>,
     journey_platform: ResourceStorage<JourneyPlatform>,
     platforms: ResourceStorage<Platform>,
     through_service: ResourceStorage<ThroughService>,
+    trip_patterns: ResourceStorage<TripPattern>,
 
     // Exchange times
     exchange_times_administration: ResourceStorage<ExchangeTimeAdministration>,
@@ -51,14 +57,23 @@ pub struct DataStorage {
     exchange_times_line: ResourceStorage<ExchangeTimeLine>,
 
     // Maps
-    bit_fields_by_day: FxHashMap<NaiveDate, FxHashSet<i32>>,
+    bit_fields_by_day: BitFieldsByDay,
     bit_fields_by_stop_id: FxHashMap<i32, FxHashSet<i32>>,
     journeys_by_stop_id_and_bit_field_id: FxHashMap<(i32, i32), Vec<i32>>,
-    stop_connections_by_stop_id: FxHashMap<i32, FxHashSet<i32>>,
+    stop_connections_by_stop_id: FxHashMap<i32, FxHashSet<i32>>, // stop_id_1 => stop connection ids
+    undirected_stop_connections_by_stop_id: FxHashMap<i32, FxHashSet<i32>>, // stop_id_1 or stop_id_2 => stop connection ids
+    stop_groups_by_stop_id: FxHashMap<i32, Vec<i32>>, // stop_id => ids of the meta stops containing the stop
     bit_field_id_for_through_service_by_journey_id_stop_id:
         FxHashMap<(JourneyId, JourneyId, i32), i32>,
     exchange_times_administration_map: FxHashMap<(Option<i32>, String, String), i32>,
-    exchange_times_journey_map: FxHashMap<(i32, JourneyId, JourneyId), FxHashSet<i32>>,
+    exchange_times_journey_map: FxHashMap<(i32, i32, i32), FxHashSet<i32>>, // (stop_id, journey_id_1, journey_id_2) => exchange time ids
+    exchange_times_line_by_stop_id: FxHashMap<Option<i32>, Vec<i32>>, // stop_id (None for all stops) => exchange time ids
+    trip_pattern_by_journey_id: FxHashMap<i32, i32>,
+    journeys_by_legacy_id: FxHashMap<i32, Vec<i32>>,
+    journey_platforms_by_journey_id: FxHashMap<i32, Vec<i32>>, // journey_id => journey_platform ids
+    trip_patterns_by_stop_id: FxHashMap<i32, Vec<(i32, usize)>>, // stop_id => (trip_pattern_id, index of the stop in the pattern)
+    through_services_by_journey_id: FxHashMap<i32, Vec<(i32, i32)>>, // journey_id (journey 1) => (through service id, journey_id of the journey 2)
+    holidays_by_date: FxHashMap<NaiveDate, i32>,                     // date => holiday id
 
     // Additional global data
     default_exchange_time: (i16, i16), // (InterCity exchange time, Exchange time for all other journey types)
@@ -70,6 +85,7 @@ impl DataStorage {
         let bit_fields = parsing::load_bit_fields(path)?;
         let holidays = parsing::load_holidays(path)?;
         let timetable_metadata = parsing::load_timetable_metadata(path)?;
+        let timetable_info = timetable_info(&timetable_metadata)?;
 
         // Basic data
         let (attributes, attributes_pk_type_converter) = parsing::load_attributes(path)?;
@@ -77,11 +93,14 @@ impl DataStorage {
         let information_texts = parsing::load_information_texts(path)?;
         let lines = parsing::load_lines(path)?;
         let transport_companies = parsing::load_transport_companies(path)?;
-        let (transport_types, transport_types_pk_type_converter) = parsing::load_transport_types(path)?;
+        let (transport_types, transport_types_pk_type_converter) =
+            parsing::load_transport_types(path)?;
 
         // Stop data
-        let stop_connections = parsing::load_stop_connections(path, &attributes_pk_type_converter)?;
+        let (stop_connections, stop_groups) =
+            parsing::load_stop_connections(path, &attributes_pk_type_converter)?;
         let (stops, default_exchange_time) = parsing::load_stops(version, path)?;
+        let border_points = parsing::load_border_points(path)?;
 
         // Timetable data
         let (journeys, journeys_pk_type_converter) = parsing::load_journeys(
@@ -89,20 +108,21 @@ impl DataStorage {
             &transport_types_pk_type_converter,
             &attributes_pk_type_converter,
             &directions_pk_type_converter,
+            &information_texts,
         )?;
         let (journey_platform, platforms) =
-            parsing::load_platforms(version, path, &journeys_pk_type_converter)?;
+            parsing::load_platforms(version, path, &journeys_pk_type_converter, &journeys)?;
         let through_service = parsing::load_through_service(path, &journeys_pk_type_converter)?;
 
         // Exchange times
         let exchange_times_administration = parsing::load_exchange_times_administration(path)?;
         let exchange_times_journey =
-            parsing::load_exchange_times_journey(path, &journeys_pk_type_converter)?;
+            parsing::load_exchange_times_journey(path, &journeys_pk_type_converter, &journeys)?;
         let exchange_times_line =
             parsing::load_exchange_times_line(path, &transport_types_pk_type_converter)?;
 
         log::info!("Building bit_fields_by_day...");
-        let bit_fields_by_day = create_bit_fields_by_day(&bit_fields, &timetable_metadata)?;
+        let bit_fields_by_day = create_bit_fields_by_day(&bit_fields, timetable_info.period());
         log::info!("Building bit_fields_by_stop_id...");
         let bit_fields_by_stop_id = create_bit_fields_by_stop_id(&journeys);
         log::info!("Building journeys by stop id and bit field_id...");
@@ -113,18 +133,43 @@ impl DataStorage {
             create_bit_field_id_through_service_by_journey_id_stop_id(&through_service);
         log::info!("Building stop connections by stop id...");
         let stop_connections_by_stop_id = create_stop_connections_by_stop_id(&stop_connections);
+        let undirected_stop_connections_by_stop_id =
+            create_undirected_stop_connections_by_stop_id(&stop_connections);
+        log::info!("Building stop groups by stop id...");
+        let stop_groups_by_stop_id = create_stop_groups_by_stop_id(&stop_groups);
         log::info!("Building exchange times administration map...");
         let exchange_times_administration_map =
             create_exchange_times_administration_map(&exchange_times_administration);
         log::info!("Building exchange times journey_map...");
         let exchange_times_journey_map = create_exchange_times_journey_map(&exchange_times_journey);
-        log::info!("Building through service map...");
+        log::info!("Building exchange times line by stop id...");
+        let exchange_times_line_by_stop_id =
+            create_exchange_times_line_by_stop_id(&exchange_times_line);
+        log::info!("Building trip patterns...");
+        let trip_patterns = create_trip_patterns(&journeys);
+        let trip_pattern_by_journey_id = create_trip_pattern_by_journey_id(&trip_patterns);
+        log::info!("Building journeys by legacy id...");
+        let journeys_by_legacy_id = create_journeys_by_legacy_id(&journeys);
+        log::info!("Building journey platforms by journey id...");
+        let journey_platforms_by_journey_id =
+            create_journey_platforms_by_journey_id(&journey_platform);
+        log::info!("Building trip patterns by stop id...");
+        let trip_patterns_by_stop_id = create_trip_patterns_by_stop_id(&trip_patterns);
+        log::info!("Building through services by journey id...");
+        let through_services_by_journey_id = create_through_services_by_journey_id(
+            &through_service,
+            &journeys,
+            &journeys_by_legacy_id,
+        );
+        log::info!("Building holidays by date...");
+        let holidays_by_date = create_holidays_by_date(&holidays);
 
         let data_storage = Self {
             // Time-relevant data
             bit_fields,
             holidays,
             timetable_metadata,
+            timetable_info,
             // Basic data
             attributes,
             information_texts,
@@ -134,12 +179,15 @@ impl DataStorage {
             transport_types,
             // Stop data
             stop_connections,
+            stop_groups,
             stops,
+            border_points,
             // Timetable data
             journeys,
             journey_platform,
             platforms,
             through_service,
+            trip_patterns,
             // Exchange times
             exchange_times_administration,
             exchange_times_journey,
@@ -149,9 +197,18 @@ impl DataStorage {
             bit_fields_by_stop_id,
             journeys_by_stop_id_and_bit_field_id,
             stop_connections_by_stop_id,
+            undirected_stop_connections_by_stop_id,
+            stop_groups_by_stop_id,
             bit_field_id_for_through_service_by_journey_id_stop_id,
             exchange_times_administration_map,
             exchange_times_journey_map,
+            exchange_times_line_by_stop_id,
+            trip_pattern_by_journey_id,
+            journeys_by_legacy_id,
+            journey_platforms_by_journey_id,
+            trip_patterns_by_stop_id,
+            through_services_by_journey_id,
+            holidays_by_date,
             // Additional global data
             default_exchange_time,
         };
@@ -161,14 +218,38 @@ impl DataStorage {
 
     // Getters/Setters
 
+    pub fn attributes(&self) -> &ResourceStorage<Attribute> {
+        &self.attributes
+    }
+
     pub fn bit_fields(&self) -> &ResourceStorage<BitField> {
         &self.bit_fields
     }
 
+    pub fn border_points(&self) -> &ResourceStorage<BorderPoint> {
+        &self.border_points
+    }
+
+    pub fn holidays(&self) -> &ResourceStorage<Holiday> {
+        &self.holidays
+    }
+
+    pub fn directions(&self) -> &ResourceStorage<Direction> {
+        &self.directions
+    }
+
+    pub fn information_texts(&self) -> &ResourceStorage<InformationText> {
+        &self.information_texts
+    }
+
     pub fn journeys(&self) -> &ResourceStorage<Journey> {
         &self.journeys
     }
 
+    pub fn journey_platform(&self) -> &ResourceStorage<JourneyPlatform> {
+        &self.journey_platform
+    }
+
     pub fn lines(&self) -> &ResourceStorage<Line> {
         &self.lines
     }
@@ -181,6 +262,10 @@ impl DataStorage {
         &self.stop_connections
     }
 
+    pub fn stop_groups(&self) -> &ResourceStorage<StopGroup> {
+        &self.stop_groups
+    }
+
     pub fn through_service(&self) -> &ResourceStorage<ThroughService> {
         &self.through_service
     }
@@ -189,6 +274,10 @@ impl DataStorage {
         &self.stops
     }
 
+    pub fn trip_patterns(&self) -> &ResourceStorage<TripPattern> {
+        &self.trip_patterns
+    }
+
     pub fn transport_types(&self) -> &ResourceStorage<TransportType> {
         &self.transport_types
     }
@@ -197,6 +286,14 @@ impl DataStorage {
         &self.timetable_metadata
     }
 
+    pub fn timetable_info(&self) -> &TimetableInfo {
+        &self.timetable_info
+    }
+
+    pub fn timetable_period(&self) -> TimetablePeriod {
+        self.timetable_info.period()
+    }
+
     pub fn exchange_times_administration(&self) -> &ResourceStorage<ExchangeTimeAdministration> {
         &self.exchange_times_administration
     }
@@ -209,7 +306,7 @@ impl DataStorage {
         &self.exchange_times_line
     }
 
-    pub fn bit_fields_by_day(&self) -> &FxHashMap<NaiveDate, FxHashSet<i32>> {
+    pub fn bit_fields_by_day(&self) -> &BitFieldsByDay {
         &self.bit_fields_by_day
     }
 
@@ -225,6 +322,14 @@ impl DataStorage {
         &self.stop_connections_by_stop_id
     }
 
+    pub fn undirected_stop_connections_by_stop_id(&self) -> &FxHashMap<i32, FxHashSet<i32>> {
+        &self.undirected_stop_connections_by_stop_id
+    }
+
+    pub fn stop_groups_by_stop_id(&self) -> &FxHashMap<i32, Vec<i32>> {
+        &self.stop_groups_by_stop_id
+    }
+
     pub fn bit_field_id_for_through_service_by_journey_id_stop_id(
         &self,
     ) -> &FxHashMap<(JourneyId, JourneyId, i32), i32> {
@@ -237,15 +342,285 @@ impl DataStorage {
         &self.exchange_times_administration_map
     }
 
-    pub fn exchange_times_journey_map(
-        &self,
-    ) -> &FxHashMap<(i32, JourneyId, JourneyId), FxHashSet<i32>> {
+    pub fn exchange_times_journey_map(&self) -> &FxHashMap<(i32, i32, i32), FxHashSet<i32>> {
         &self.exchange_times_journey_map
     }
 
+    pub fn exchange_times_line_by_stop_id(&self) -> &FxHashMap<Option<i32>, Vec<i32>> {
+        &self.exchange_times_line_by_stop_id
+    }
+
+    pub fn trip_pattern_by_journey_id(&self) -> &FxHashMap<i32, i32> {
+        &self.trip_pattern_by_journey_id
+    }
+
+    pub fn journeys_by_legacy_id(&self) -> &FxHashMap<i32, Vec<i32>> {
+        &self.journeys_by_legacy_id
+    }
+
+    pub fn journey_platforms_by_journey_id(&self) -> &FxHashMap<i32, Vec<i32>> {
+        &self.journey_platforms_by_journey_id
+    }
+
+    pub fn trip_patterns_by_stop_id(&self) -> &FxHashMap<i32, Vec<(i32, usize)>> {
+        &self.trip_patterns_by_stop_id
+    }
+
+    pub fn through_services_by_journey_id(&self) -> &FxHashMap<i32, Vec<(i32, i32)>> {
+        &self.through_services_by_journey_id
+    }
+
+    pub fn holidays_by_date(&self) -> &FxHashMap<NaiveDate, i32> {
+        &self.holidays_by_date
+    }
+
     pub fn default_exchange_time(&self) -> (i16, i16) {
         self.default_exchange_time
     }
+
+    // Functions
+
+    /// Returns the journeys matching a public trip designation, e.g. "IC 1 712" is searched with
+    /// (Some("IC"), Some("1"), Some(712), None). Every criterion is optional, the designations are case-insensitive.
+    /// If a date is given, only the journeys operating on that day are returned.
+    /// Several journeys can match the same designation (e.g. the same number used by several transport companies),
+    /// they are all returned, sorted by id.
+    pub fn find_journeys(
+        &self,
+        transport_type_designation: Option<&str>,
+        line_designation: Option<&str>,
+        number: Option<i32>,
+        date: Option<NaiveDate>,
+    ) -> Vec<&Journey> {
+        let candidates: Vec<&Journey> = match number {
+            Some(number) => self
+                .journeys_by_legacy_id
+                .get(&number)
+                .map(|ids| {
+                    ids.iter()
+                        .filter_map(|&id| self.journeys.find(id))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            None => self.journeys.entries(),
+        };
+
+        let mut journeys: Vec<_> = candidates
+            .into_iter()
+            .filter(|journey| {
+                transport_type_designation.is_none_or(|designation| {
+                    journey
+                        .transport_type(self)
+                        .designation()
+                        .eq_ignore_ascii_case(designation.trim())
+                })
+            })
+            .filter(|journey| {
+                line_designation.is_none_or(|designation| {
+                    journey
+                        .line_designation(self)
+                        .is_some_and(|line| line.eq_ignore_ascii_case(designation.trim()))
+                })
+            })
+            .filter(|journey| date.is_none_or(|date| journey.operates_on(date, self)))
+            .collect();
+        journeys.sort_by_key(|journey| journey.id());
+        journeys
+    }
+
+    /// Returns the public holiday (FEIERTAG file) falling on the date, if any.
+    pub fn holiday_on(&self, date: NaiveDate) -> Option<&Holiday> {
+        let holiday_id = self.holidays_by_date.get(&date)?;
+        self.holidays.find(*holiday_id)
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holiday_on(date).is_some()
+    }
+
+    /// Returns the public holidays within the timetable period, sorted by date.
+    pub fn holidays_in_timetable_period(&self) -> Vec<&Holiday> {
+        let period = self.timetable_period();
+
+        let mut holidays: Vec<_> = self
+            .holidays
+            .entries()
+            .into_iter()
+            .filter(|holiday| period.contains(holiday.date()))
+            .collect();
+        holidays.sort_by_key(|holiday| (holiday.date(), holiday.id()));
+        holidays
+    }
+
+    /// Returns the meta stops (METABHF file) containing the stop, sorted by id.
+    pub fn stop_groups_of(&self, stop_id: i32) -> Vec<&StopGroup> {
+        self.stop_groups_by_stop_id
+            .get(&stop_id)
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|&id| self.stop_groups.find(id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the stops grouped by the meta stop, or the stop itself if it is not a meta stop.
+    pub fn resolve_stop_ids(&self, stop_id: i32) -> Vec<i32> {
+        self.stop_groups
+            .find(stop_id)
+            .map_or_else(|| vec![stop_id], |stop_group| stop_group.stop_ids().clone())
+    }
+
+    /// Returns the day type of the date, the public holidays being classified as Sundays.
+    pub fn day_type(&self, date: NaiveDate) -> DayType {
+        DayType::new(date, self.is_holiday(date))
+    }
+
+    /// Returns the data storage restricted to the dates from start_date to end_date,
+    /// which must be within the timetable period.
+    /// The day before start_date is kept too (if it is within the timetable period), as its journeys can still run
+    /// after midnight: it becomes the start date of the new timetable period.
+    /// The bit fields are re-based so that this date is their day 0. The bit fields without any operating day
+    /// are removed, along with the journeys (and their platforms, exchange times and through services)
+    /// which do not operate in the date range.
+    pub fn clip(
+        mut self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Self, Box<dyn Error>> {
+        let period = self.timetable_period();
+        if !period.contains(start_date) || !period.contains(end_date) || start_date > end_date {
+            return Err("The date range must be within the timetable period.".into());
+        }
+
+        // Time-relevant data
+        let start_date = if start_date > period.start_date() {
+            sub_1_day(start_date)
+        } else {
+            start_date
+        };
+        let start_day = count_days_between_two_dates(period.start_date(), start_date) - 1;
+        let num_days = count_days_between_two_dates(start_date, end_date);
+        let (bit_fields, empty_bit_fields): (Vec<_>, Vec<_>) = self
+            .bit_fields
+            .entries()
+            .into_iter()
+            .map(|bit_field| bit_field.rebased(start_day, num_days))
+            .partition(|bit_field| !bit_field.is_empty());
+        self.bit_fields = ResourceStorage::new(BitField::vec_to_map(bit_fields));
+        self.holidays
+            .retain(|holiday| start_date <= holiday.date() && holiday.date() <= end_date);
+        let timetable_metadata = self
+            .timetable_metadata
+            .entries()
+            .into_iter()
+            .map(|entry| {
+                let value = match entry.key() {
+                    "start_date" => start_date.to_string(),
+                    "end_date" => end_date.to_string(),
+                    _ => entry.value().to_string(),
+                };
+                TimetableMetadataEntry::new(entry.id(), entry.key().to_string(), value)
+            })
+            .collect();
+        self.timetable_metadata =
+            ResourceStorage::new(TimetableMetadataEntry::vec_to_map(timetable_metadata));
+        self.timetable_info
+            .set_period(TimetablePeriod::new(start_date, end_date)?);
+
+        // The bit field 0 means that the entry is valid every day.
+        let bit_fields = &self.bit_fields;
+        let is_operating = |bit_field_id: Option<i32>| {
+            bit_field_id.is_none_or(|id| id == 0 || bit_fields.find(id).is_some())
+        };
+
+        // Timetable data
+        self.journeys
+            .retain(|journey| is_operating(journey.bit_field_id()));
+        // The entries of a removed bit field never apply, except the *SH ones: their stop is not served at all.
+        // The empty bit fields they refer to are kept.
+        let mut stop_bit_field_ids = FxHashSet::default();
+        for journey in self.journeys.entries_mut() {
+            journey.retain_metadata_entries(|k, entry| {
+                if k == JourneyMetadataType::StopBitField {
+                    stop_bit_field_ids.extend(entry.bit_field_id());
+                    true
+                } else {
+                    is_operating(entry.bit_field_id())
+                }
+            });
+        }
+        let journeys = &self.journeys;
+        self.journey_platform.retain(|journey_platform| {
+            journeys.find(journey_platform.journey_id()).is_some()
+                && is_operating(journey_platform.bit_field_id())
+        });
+        let journey_ids: FxHashSet<JourneyId> = journeys
+            .entries()
+            .into_iter()
+            .map(|journey| (journey.legacy_id(), journey.administration().to_string()))
+            .collect();
+        self.through_service.retain(|through_service| {
+            journey_ids.contains(through_service.journey_1_id())
+                && journey_ids.contains(through_service.journey_2_id())
+                && is_operating(Some(through_service.bit_field_id()))
+        });
+
+        // Exchange times
+        self.exchange_times_journey.retain(|exchange_time| {
+            journeys.find(exchange_time.journey_id_1()).is_some()
+                && journeys.find(exchange_time.journey_id_2()).is_some()
+                && is_operating(exchange_time.bit_field_id())
+        });
+
+        for bit_field in empty_bit_fields {
+            if stop_bit_field_ids.contains(&bit_field.id()) {
+                self.bit_fields.insert(bit_field);
+            }
+        }
+
+        // Maps
+        self.bit_fields_by_day =
+            create_bit_fields_by_day(&self.bit_fields, self.timetable_info.period());
+        self.bit_fields_by_stop_id = create_bit_fields_by_stop_id(&self.journeys);
+        self.journeys_by_stop_id_and_bit_field_id =
+            create_journeys_by_stop_id_and_bit_field_id(&self.journeys);
+        self.bit_field_id_for_through_service_by_journey_id_stop_id =
+            create_bit_field_id_through_service_by_journey_id_stop_id(&self.through_service);
+        self.exchange_times_journey_map =
+            create_exchange_times_journey_map(&self.exchange_times_journey);
+        self.trip_patterns = create_trip_patterns(&self.journeys);
+        self.trip_pattern_by_journey_id = create_trip_pattern_by_journey_id(&self.trip_patterns);
+        self.journeys_by_legacy_id = create_journeys_by_legacy_id(&self.journeys);
+        self.journey_platforms_by_journey_id =
+            create_journey_platforms_by_journey_id(&self.journey_platform);
+        self.trip_patterns_by_stop_id = create_trip_patterns_by_stop_id(&self.trip_patterns);
+        self.through_services_by_journey_id = create_through_services_by_journey_id(
+            &self.through_service,
+            &self.journeys,
+            &self.journeys_by_legacy_id,
+        );
+        self.holidays_by_date = create_holidays_by_date(&self.holidays);
+
+        Ok(self)
+    }
+
+    /// Adds the connections to the stop connections and to their index.
+    pub(crate) fn add_stop_connections(&mut self, stop_connections: Vec<StopConnection>) {
+        for stop_connection in stop_connections {
+            self.stop_connections_by_stop_id
+                .entry(stop_connection.stop_id_1())
+                .or_default()
+                .insert(stop_connection.id());
+            for stop_id in [stop_connection.stop_id_1(), stop_connection.stop_id_2()] {
+                self.undirected_stop_connections_by_stop_id
+                    .entry(stop_id)
+                    .or_default()
+                    .insert(stop_connection.id());
+            }
+            self.stop_connections.insert(stop_connection);
+        }
+    }
 }
 
 // ------------------------------------------------------------------------------------------------
@@ -275,6 +650,18 @@ impl<M: Model<M>> ResourceStorage<M> {
         self.data.values().collect()
     }
 
+    pub fn entries_mut(&mut self) -> Vec<&mut M> {
+        self.data.values_mut().collect()
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&M) -> bool) {
+        self.data.retain(|_, item| f(item));
+    }
+
+    pub fn insert(&mut self, item: M) {
+        self.data.insert(item.id(), item);
+    }
+
     pub fn resolve_ids(&self, ids: &FxHashSet<M::K>) -> Option<Vec<&M>> {
         ids.iter().map(|&id| self.find(id)).collect()
     }
@@ -286,48 +673,13 @@ impl<M: Model<M>> ResourceStorage<M> {
 
 fn create_bit_fields_by_day(
     bit_fields: &ResourceStorage<BitField>,
-    timetable_metadata: &ResourceStorage<TimetableMetadataEntry>,
-) -> Result<FxHashMap<NaiveDate, FxHashSet<i32>>, Box<dyn Error>> {
-    let start_date = timetable_start_date(timetable_metadata)?;
-    let num_days =
-        count_days_between_two_dates(start_date, timetable_end_date(timetable_metadata)?);
-
-    let dates: Vec<NaiveDate> = (0..num_days)
-        .map(|i| {
-            start_date
-                // unwrap: Converting i from usize to u64 will never fail.
-                .checked_add_days(Days::new(i.try_into().unwrap()))
-                // unwrap: Adding days will never fail.
-                .unwrap()
-        })
-        .collect();
-
-    let mut map = FxHashMap::default();
-    dates.iter().for_each(|date| {
-        map.entry(*date).or_insert(FxHashSet::default()).insert(0);
-    });
-
-    let result = bit_fields.data().keys().fold(map, |mut acc, bit_field_id| {
-        let bit_field = bit_fields
-            .find(*bit_field_id)
-            .unwrap_or_else(|| panic!("Bitfield id {:?} not found.", bit_field_id));
-        let indexes: Vec<usize> = bit_field
-            .bits()
-            .iter()
-            // The first two bits must be ignored.
-            .skip(2)
-            .enumerate()
-            .filter(|&(ref i, &x)| *i < num_days && x == 1)
-            .map(|(i, _)| i)
-            .collect();
-
-        indexes.iter().for_each(|&i| {
-            acc.entry(dates[i]).or_default().insert(bit_field.id());
-        });
-
-        acc
-    });
-    Ok(result)
+    timetable_period: TimetablePeriod,
+) -> BitFieldsByDay {
+    BitFieldsByDay::new(
+        bit_fields.entries(),
+        timetable_period.start_date(),
+        timetable_period.end_date(),
+    )
 }
 
 fn create_bit_fields_by_stop_id(
@@ -399,22 +751,50 @@ fn create_stop_connections_by_stop_id(
         })
 }
 
+/// A connection is indexed under both of its stops.
+fn create_undirected_stop_connections_by_stop_id(
+    stop_connections: &ResourceStorage<StopConnection>,
+) -> FxHashMap<i32, FxHashSet<i32>> {
+    stop_connections
+        .entries()
+        .into_iter()
+        .fold(FxHashMap::default(), |mut acc, stop_connection| {
+            for stop_id in [stop_connection.stop_id_1(), stop_connection.stop_id_2()] {
+                acc.entry(stop_id).or_default().insert(stop_connection.id());
+            }
+            acc
+        })
+}
+
+fn create_stop_groups_by_stop_id(
+    stop_groups: &ResourceStorage<StopGroup>,
+) -> FxHashMap<i32, Vec<i32>> {
+    let mut stop_groups_by_stop_id = stop_groups.entries().into_iter().fold(
+        FxHashMap::default(),
+        |mut acc: FxHashMap<i32, Vec<i32>>, stop_group| {
+            for &stop_id in stop_group.stop_ids() {
+                acc.entry(stop_id).or_default().push(stop_group.id());
+            }
+            acc
+        },
+    );
+    stop_groups_by_stop_id
+        .values_mut()
+        .for_each(|stop_group_ids| stop_group_ids.sort_unstable());
+    stop_groups_by_stop_id
+}
+
 fn create_exchange_times_journey_map(
     exchange_times_journey: &ResourceStorage<ExchangeTimeJourney>,
-) -> FxHashMap<(i32, JourneyId, JourneyId), FxHashSet<i32>> {
+) -> FxHashMap<(i32, i32, i32), FxHashSet<i32>> {
     exchange_times_journey.entries().into_iter().fold(
         FxHashMap::default(),
         |mut acc, exchange_time| {
+            // The ids of the journeys are used, since the (legacy_id, administration) pairs are not unique for the TU code 801.
             let key = (
                 exchange_time.stop_id(),
-                (
-                    exchange_time.journey_legacy_id_1(),
-                    exchange_time.administration_1().to_string(),
-                ),
-                (
-                    exchange_time.journey_legacy_id_2(),
-                    exchange_time.administration_2().to_string(),
-                ),
+                exchange_time.journey_id_1(),
+                exchange_time.journey_id_2(),
             );
 
             acc.entry(key).or_default().insert(exchange_time.id());
@@ -423,6 +803,23 @@ fn create_exchange_times_journey_map(
     )
 }
 
+fn create_exchange_times_line_by_stop_id(
+    exchange_times_line: &ResourceStorage<ExchangeTimeLine>,
+) -> FxHashMap<Option<i32>, Vec<i32>> {
+    let mut map = exchange_times_line.entries().into_iter().fold(
+        FxHashMap::default(),
+        |mut acc: FxHashMap<Option<i32>, Vec<i32>>, exchange_time| {
+            acc.entry(exchange_time.stop_id())
+                .or_default()
+                .push(exchange_time.id());
+            acc
+        },
+    );
+    // Sorted to get the same result for the same data.
+    map.values_mut().for_each(|ids| ids.sort_unstable());
+    map
+}
+
 fn create_exchange_times_administration_map(
     exchange_times_administration: &ResourceStorage<ExchangeTimeAdministration>,
 ) -> FxHashMap<(Option<i32>, String, String), i32> {
@@ -440,3 +837,629 @@ fn create_exchange_times_administration_map(
         },
     )
 }
+
+/// Groups the journeys by their ordered sequence of stops.
+/// The ids are assigned in the order of the stop sequences, so they are stable for the same data.
+fn create_trip_patterns(journeys: &ResourceStorage<Journey>) -> ResourceStorage<TripPattern> {
+    let journeys_by_stop_ids = journeys.entries().into_iter().fold(
+        FxHashMap::default(),
+        |mut acc: FxHashMap<Vec<i32>, Vec<&Journey>>, journey| {
+            let stop_ids = journey
+                .route()
+                .iter()
+                .map(|route_entry| route_entry.stop_id())
+                .collect();
+            acc.entry(stop_ids).or_default().push(journey);
+            acc
+        },
+    );
+
+    let mut journeys_by_stop_ids: Vec<_> = journeys_by_stop_ids.into_iter().collect();
+    journeys_by_stop_ids.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+    let trip_patterns = journeys_by_stop_ids
+        .into_iter()
+        .enumerate()
+        .map(|(i, (stop_ids, mut journeys))| {
+            // unwrap: The first route entry always has a departure time.
+            journeys.sort_by_key(|journey| {
+                (journey.route()[0].departure_time().unwrap(), journey.id())
+            });
+
+            let journey_ids = journeys.iter().map(|journey| journey.id()).collect();
+            let offsets = journeys
+                .iter()
+                .map(|journey| journey.stop_offsets())
+                .collect();
+            // unwrap: The number of patterns always fits in an i32.
+            let id = i32::try_from(i + 1).unwrap();
+            TripPattern::new(id, stop_ids, journey_ids, offsets)
+        })
+        .collect();
+
+    ResourceStorage::new(TripPattern::vec_to_map(trip_patterns))
+}
+
+fn create_trip_pattern_by_journey_id(
+    trip_patterns: &ResourceStorage<TripPattern>,
+) -> FxHashMap<i32, i32> {
+    trip_patterns
+        .entries()
+        .into_iter()
+        .fold(FxHashMap::default(), |mut acc, trip_pattern| {
+            trip_pattern.journey_ids().iter().for_each(|&journey_id| {
+                acc.insert(journey_id, trip_pattern.id());
+            });
+            acc
+        })
+}
+
+fn create_trip_patterns_by_stop_id(
+    trip_patterns: &ResourceStorage<TripPattern>,
+) -> FxHashMap<i32, Vec<(i32, usize)>> {
+    trip_patterns
+        .entries()
+        .into_iter()
+        .fold(FxHashMap::default(), |mut acc, trip_pattern| {
+            // A stop can appear several times in the same pattern (loops).
+            trip_pattern
+                .stop_ids()
+                .iter()
+                .enumerate()
+                .for_each(|(i, &stop_id)| {
+                    acc.entry(stop_id).or_default().push((trip_pattern.id(), i));
+                });
+            acc
+        })
+}
+
+/// The through services are defined with (legacy_id, administration) pairs, which are resolved to the journeys serving the stop.
+/// The through services whose journeys can't be told apart are skipped.
+fn create_through_services_by_journey_id(
+    through_service: &ResourceStorage<ThroughService>,
+    journeys: &ResourceStorage<Journey>,
+    journeys_by_legacy_id: &FxHashMap<i32, Vec<i32>>,
+) -> FxHashMap<i32, Vec<(i32, i32)>> {
+    through_service
+        .entries()
+        .into_iter()
+        .fold(FxHashMap::default(), |mut acc, through_service| {
+            let journey_1_id = resolve_through_service_journey(
+                journeys,
+                journeys_by_legacy_id,
+                through_service.journey_1_id(),
+                through_service.journey_1_stop_id(),
+                |route| route.last(),
+            );
+            let journey_2_id = resolve_through_service_journey(
+                journeys,
+                journeys_by_legacy_id,
+                through_service.journey_2_id(),
+                through_service.journey_2_stop_id(),
+                |route| route.first(),
+            );
+
+            match (journey_1_id, journey_2_id) {
+                (Some(journey_1_id), Some(journey_2_id)) => {
+                    acc.entry(journey_1_id)
+                        .or_default()
+                        .push((through_service.id(), journey_2_id));
+                }
+                _ => log::warn!(
+                    "The journeys of the through service {} can't be resolved, it is skipped.",
+                    through_service.id()
+                ),
+            }
+            acc
+        })
+}
+
+/// Returns the id of the journey identified by (legacy_id, administration) which serves the stop.
+/// The journey numbers of the TU code 801 are only unique within a region, which DURCHBI doesn't contain: if several
+/// journeys serve the stop, the only one ending (journey 1) or starting (journey 2) there is kept.
+fn resolve_through_service_journey(
+    journeys: &ResourceStorage<Journey>,
+    journeys_by_legacy_id: &FxHashMap<i32, Vec<i32>>,
+    (legacy_id, administration): &JourneyId,
+    stop_id: i32,
+    end_of_route: fn(&[JourneyRouteEntry]) -> Option<&JourneyRouteEntry>,
+) -> Option<i32> {
+    let candidates: Vec<_> = journeys_by_legacy_id
+        .get(legacy_id)
+        .into_iter()
+        .flatten()
+        .filter_map(|&journey_id| journeys.find(journey_id))
+        .filter(|journey| {
+            journey.administration() == administration
+                && journey
+                    .route()
+                    .iter()
+                    .any(|route_entry| route_entry.stop_id() == stop_id)
+        })
+        .collect();
+
+    match candidates[..] {
+        [journey] => Some(journey.id()),
+        [] => None,
+        _ => {
+            let mut candidates = candidates.into_iter().filter(|journey| {
+                end_of_route(journey.route())
+                    .is_some_and(|route_entry| route_entry.stop_id() == stop_id)
+            });
+            match (candidates.next(), candidates.next()) {
+                (Some(journey), None) => Some(journey.id()),
+                _ => None,
+            }
+        }
+    }
+}
+
+fn create_journeys_by_legacy_id(journeys: &ResourceStorage<Journey>) -> FxHashMap<i32, Vec<i32>> {
+    journeys
+        .entries()
+        .into_iter()
+        .fold(FxHashMap::default(), |mut acc, journey| {
+            acc.entry(journey.legacy_id())
+                .or_default()
+                .push(journey.id());
+            acc
+        })
+}
+
+fn create_journey_platforms_by_journey_id(
+    journey_platform: &ResourceStorage<JourneyPlatform>,
+) -> FxHashMap<i32, Vec<i32>> {
+    journey_platform.entries().into_iter().fold(
+        FxHashMap::default(),
+        |mut acc, journey_platform| {
+            acc.entry(journey_platform.journey_id())
+                .or_default()
+                .push(journey_platform.id());
+            acc
+        },
+    )
+}
+
+/// If several holidays fall on the same date, the one with the smallest id is kept.
+fn create_holidays_by_date(holidays: &ResourceStorage<Holiday>) -> FxHashMap<NaiveDate, i32> {
+    holidays
+        .entries()
+        .into_iter()
+        .fold(FxHashMap::default(), |mut acc, holiday| {
+            acc.entry(holiday.date())
+                .and_modify(|id: &mut i32| *id = (*id).min(holiday.id()))
+                .or_insert(holiday.id());
+            acc
+        })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        fs,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::{
+        models::Language,
+        utils::{timetable_end_date, timetable_start_date},
+    };
+    use chrono::TimeDelta;
+
+    const DEFAULT_FILES: [(&str, &[&str]); 3] = [
+        (
+            "ECKDATEN",
+            &[
+                "15.12.2024",
+                "13.12.2025",
+                "Fahrplan 2025$02.12.2024 15:23:54$5.40.41$INFO+",
+            ],
+        ),
+        (
+            "ZUGART",
+            &["IC   1 A 0 IC       0 N", "B    6 A 0 B        0 N"],
+        ),
+        ("UMSTEIGB", &["9999999 05 02"]),
+    ];
+
+    const FILES: [&str; 33] = [
+        "BITFELD",
+        "FEIERTAG",
+        "ATTRIBUT",
+        "RICHTUNG",
+        "INFOTEXT_DE",
+        "INFOTEXT_EN",
+        "INFOTEXT_FR",
+        "INFOTEXT_IT",
+        "LINIE",
+        "BETRIEB_DE",
+        "BETRIEB_EN",
+        "BETRIEB_FR",
+        "BETRIEB_IT",
+        "METABHF",
+        "BAHNHOF",
+        "BFKOORD_LV95",
+        "BFKOORD_WGS",
+        "BFPRIOS",
+        "KMINFO",
+        "BHFART",
+        "FPLAN",
+        "GLEIS",
+        "GLEIS_LV95",
+        "GLEIS_WGS",
+        "GLEISE_LV95",
+        "GLEISE_WGS",
+        "DURCHBI",
+        "UMSTEIGV",
+        "UMSTEIGZ",
+        "UMSTEIGL",
+        "ECKDATEN",
+        "ZUGART",
+        "UMSTEIGB",
+    ];
+
+    /// Builds a data storage (version 2.0.7) from the rows of a few files, the other files are empty.
+    /// By default, the timetable goes from 15.12.2024 to 13.12.2025, the transport types are IC and B
+    /// and the default exchange times are 5 (IC) and 2 minutes.
+    pub(crate) fn data_storage_from(files: &[(&str, &[&str])]) -> DataStorage {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "hrdf_parser_test_{}_{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+
+        for file in FILES {
+            let rows = files
+                .iter()
+                .chain(DEFAULT_FILES.iter())
+                .find(|(name, _)| *name == file)
+                .map_or(&[][..], |(_, rows)| *rows);
+            // The byte offsets of some parsers (GLEIS) assume the CRLF line endings of the HRDF files.
+            fs::write(path.join(file), rows.join("\r\n")).unwrap();
+        }
+
+        let data_storage = DataStorage::new(Version::V_5_40_41_2_0_7, path.to_str().unwrap());
+        fs::remove_dir_all(&path).unwrap();
+        data_storage.unwrap()
+    }
+
+    #[test]
+    fn data_storage_from_v207() {
+        let data_storage = data_storage_from(&[
+            (
+                "BAHNHOF",
+                &["8500010     Basel SBB$<1>", "8503000     Zürich HB$<1>"],
+            ),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G IC  8500010 8503000                                     %",
+                    "*A VE 8500010 8503000 000000                               %",
+                    "8500010 Basel SBB                    00800                 %",
+                    "8503000 Zürich HB             00900                        %",
+                ],
+            ),
+        ]);
+        assert_eq!(2, data_storage.stops().entries().len());
+        assert_eq!(1, data_storage.journeys().entries().len());
+    }
+
+    fn clip_fixture() -> DataStorage {
+        data_storage_from(&[
+            (
+                "BAHNHOF",
+                &[
+                    "8500010     A$<1>",
+                    "8500020     B$<1>",
+                    "8500080     H$<1>",
+                    "8500090     I$<1>",
+                ],
+            ),
+            // Only on 03.03.2025 (day 78).
+            (
+                "BITFELD",
+                &[
+                    "000001 000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000",
+                    "000002 000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000",
+                ],
+            ),
+            (
+                "FEIERTAG",
+                &[
+                    "25.12.2024 Weihnachtstag<deu>Noël<fra>Natale<ita>Christmas Day<eng>",
+                    "04.03.2025 Fasnacht<deu>Carnaval<fra>Carnevale<ita>Carnival<eng>",
+                ],
+            ),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G IC  8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "*I hi 8500010 8500020 000001 000000001                     %",
+                    "*SH 8500020 000002                                         %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00830                        %",
+                    "*Z 000002 000011                                           %",
+                    "*G B   8500080 8500090                                     %",
+                    "*A VE 8500080 8500090 000001                               %",
+                    "8500080 H                            02330                 %",
+                    "8500090 I                     02410                        %",
+                ],
+            ),
+        ])
+    }
+
+    #[test]
+    fn clip_keeps_previous_service_day_v207() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let christmas = NaiveDate::from_ymd_opt(2024, 12, 25).unwrap();
+        let data_storage = clip_fixture();
+        assert!(data_storage.is_holiday(christmas));
+        let data_storage = data_storage.clip(date(4), date(6)).unwrap();
+
+        // The day before the start date is kept for the journeys running after midnight.
+        let period = data_storage.timetable_period();
+        assert_eq!((date(3), date(6)), (period.start_date(), period.end_date()));
+        assert_eq!(
+            (date(3), date(6)),
+            (
+                timetable_start_date(data_storage.timetable_metadata()).unwrap(),
+                timetable_end_date(data_storage.timetable_metadata()).unwrap()
+            )
+        );
+        let bit_field = data_storage.bit_fields().find(1).unwrap();
+        assert_eq!(
+            vec![date(3)],
+            bit_field
+                .operating_dates(period.start_date(), period.end_date())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2, data_storage.journeys().entries().len());
+
+        // The rebuilt indexes.
+        let from = date(4).and_hms_opt(0, 0, 0).unwrap();
+        let arrivals = data_storage.arrivals(8500090, from, TimeDelta::hours(1));
+        assert_eq!(1, arrivals.len());
+        assert_eq!(date(3), arrivals[0].service_date());
+        assert_eq!(date(4).and_hms_opt(0, 10, 0).unwrap(), arrivals[0].time());
+        let from = date(6).and_hms_opt(7, 0, 0).unwrap();
+        assert_eq!(
+            1,
+            data_storage
+                .departures(8500010, from, TimeDelta::hours(2))
+                .len()
+        );
+        let from = date(7).and_hms_opt(7, 0, 0).unwrap();
+        assert!(
+            data_storage
+                .departures(8500010, from, TimeDelta::hours(2))
+                .is_empty()
+        );
+        assert!(data_storage.trip_pattern_by_journey_id().contains_key(&2));
+        assert!(!data_storage.is_holiday(christmas));
+        assert!(data_storage.is_holiday(date(4)));
+        assert!(!data_storage.is_holiday(date(5)));
+    }
+
+    #[test]
+    fn clip_removes_journeys_not_operating_v207() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let data_storage = clip_fixture().clip(date(5), date(6)).unwrap();
+
+        assert!(data_storage.bit_fields().find(1).is_none());
+        let legacy_ids: Vec<_> = data_storage
+            .journeys()
+            .entries()
+            .into_iter()
+            .map(|journey| journey.legacy_id())
+            .collect();
+        assert_eq!(vec![1], legacy_ids);
+        assert!(data_storage.journeys_by_legacy_id().get(&2).is_none());
+
+        // No entry refers to a removed bit field, the empty one of the *SH line is kept.
+        let journey = data_storage.journeys().entries()[0];
+        assert!(
+            journey
+                .metadata()
+                .values()
+                .flatten()
+                .filter_map(|entry| entry.bit_field_id())
+                .all(|id| id == 0 || data_storage.bit_fields().find(id).is_some())
+        );
+        assert!(
+            journey
+                .information_texts(&data_storage, date(5), Language::German)
+                .is_empty()
+        );
+        assert_eq!(1, journey.stop_bit_fields().len());
+        assert!(!journey.serves_stop_on(8500020, date(5), &data_storage));
+
+        assert!(clip_fixture().clip(date(6), date(5)).is_err());
+        assert!(
+            clip_fixture()
+                .clip(date(1), NaiveDate::from_ymd_opt(2026, 1, 1).unwrap())
+                .is_err()
+        );
+    }
+
+    /// - IC 1 (line 1): A 09:00, B 09:30
+    /// - IC 2 (line 1): A 08:00, B 08:30
+    /// - B 3: B 07:00, A 07:30
+    /// - B 4: A 07:00, B 07:10/07:11, A 07:20 (loop)
+    /// - IC 712 (000011, line 1): A 10:00, C 11:00
+    /// - IC 712 (000022, line 2): C 12:00, A 13:00, only on 03.03.2025
+    fn journeys_fixture() -> DataStorage {
+        data_storage_from(&[
+            (
+                "BAHNHOF",
+                &[
+                    "8500010     A$<1>",
+                    "8500020     B$<1>",
+                    "8500030     C$<1>",
+                ],
+            ),
+            (
+                "BITFELD",
+                &[
+                    "000001 000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000",
+                ],
+            ),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G IC  8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "*L 1        8500010 8500020                                %",
+                    "8500010 A                            00900                 %",
+                    "8500020 B                     00930                        %",
+                    "*Z 000002 000011                                           %",
+                    "*G IC  8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "*L 1        8500010 8500020                                %",
+                    "8500010 A                            00800                 %",
+                    "8500020 B                     00830                        %",
+                    "*Z 000003 000011                                           %",
+                    "*G B   8500020 8500010                                     %",
+                    "*A VE 8500020 8500010 000000                               %",
+                    "8500020 B                            00700                 %",
+                    "8500010 A                     00730                        %",
+                    "*Z 000004 000011                                           %",
+                    "*G B   8500010 8500010                                     %",
+                    "*A VE 8500010 8500010 000000                               %",
+                    "8500010 A                            00700                 %",
+                    "8500020 B                     00710  00711                 %",
+                    "8500010 A                     00720                        %",
+                    "*Z 000712 000011                                           %",
+                    "*G IC  8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000000                               %",
+                    "*L 1        8500010 8500030                                %",
+                    "8500010 A                            01000                 %",
+                    "8500030 C                     01100                        %",
+                    "*Z 000712 000022                                           %",
+                    "*G IC  8500030 8500010                                     %",
+                    "*A VE 8500030 8500010 000001                               %",
+                    "*L 2        8500030 8500010                                %",
+                    "8500030 C                            01200                 %",
+                    "8500010 A                     01300                        %",
+                ],
+            ),
+        ])
+    }
+
+    #[test]
+    fn trip_patterns_v207() {
+        let data_storage = journeys_fixture();
+
+        // The ids follow the order of the stop sequences, so they do not change from a load to the next.
+        let mut trip_patterns: Vec<_> = data_storage
+            .trip_patterns()
+            .entries()
+            .into_iter()
+            .map(|trip_pattern| {
+                (
+                    trip_pattern.id(),
+                    trip_pattern.stop_ids().clone(),
+                    trip_pattern.journey_ids().clone(),
+                )
+            })
+            .collect();
+        trip_patterns.sort_unstable();
+        assert_eq!(
+            vec![
+                (1, vec![8500010, 8500020], vec![2, 1]),
+                (2, vec![8500010, 8500020, 8500010], vec![4]),
+                (3, vec![8500010, 8500030], vec![5]),
+                (4, vec![8500020, 8500010], vec![3]),
+                (5, vec![8500030, 8500010], vec![6]),
+            ],
+            trip_patterns
+        );
+        assert_eq!(
+            data_storage
+                .trip_patterns()
+                .entries()
+                .into_iter()
+                .map(|trip_pattern| (trip_pattern.id(), trip_pattern.stop_ids().clone()))
+                .collect::<FxHashMap<_, _>>(),
+            journeys_fixture()
+                .trip_patterns()
+                .entries()
+                .into_iter()
+                .map(|trip_pattern| (trip_pattern.id(), trip_pattern.stop_ids().clone()))
+                .collect::<FxHashMap<_, _>>()
+        );
+    }
+
+    #[test]
+    fn trip_pattern_indexes_v207() {
+        let data_storage = journeys_fixture();
+
+        assert_eq!(Some(&1), data_storage.trip_pattern_by_journey_id().get(&1));
+        assert_eq!(Some(&1), data_storage.trip_pattern_by_journey_id().get(&2));
+        let trip_pattern = data_storage.trip_patterns().find(1).unwrap();
+        assert_eq!(
+            Some(&vec![(None, Some(0)), (Some(30), None)]),
+            trip_pattern.offsets_of(1)
+        );
+        assert_eq!(None, trip_pattern.offsets_of(3));
+
+        // The loop serves A twice.
+        let mut positions = data_storage.trip_patterns_by_stop_id()[&8500010].clone();
+        positions.sort_unstable();
+        assert_eq!(
+            vec![(1, 0), (2, 0), (2, 2), (3, 0), (4, 1), (5, 1)],
+            positions
+        );
+    }
+
+    #[test]
+    fn find_journeys_v207() {
+        let data_storage = journeys_fixture();
+        let find = |transport_type, line, number, date: Option<NaiveDate>| {
+            data_storage
+                .find_journeys(transport_type, line, number, date)
+                .into_iter()
+                .map(|journey| journey.id())
+                .collect::<Vec<_>>()
+        };
+
+        // The number 712 is used by two administrations, both journeys are returned.
+        assert_eq!(vec![5, 6], find(Some("ic"), None, Some(712), None));
+        let journeys = data_storage.find_journeys(None, None, Some(712), None);
+        assert_eq!(
+            vec!["000011", "000022"],
+            journeys
+                .iter()
+                .map(|journey| journey.administration())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![6], find(Some("IC"), Some("2"), Some(712), None));
+        assert_eq!(vec![1, 2, 5], find(Some("IC"), Some(" 1 "), None, None));
+        assert_eq!(vec![3, 4], find(Some("B"), None, None, None));
+        assert!(find(Some("B"), None, Some(712), None).is_empty());
+        assert!(find(None, None, Some(713), None).is_empty());
+    }
+
+    #[test]
+    fn find_journeys_on_date_v207() {
+        let data_storage = journeys_fixture();
+        let date = |day| Some(NaiveDate::from_ymd_opt(2025, 3, day).unwrap());
+        let find = |date| {
+            data_storage
+                .find_journeys(None, None, Some(712), date)
+                .into_iter()
+                .map(|journey| journey.id())
+                .collect::<Vec<_>>()
+        };
+
+        // The journey 6 only operates on 03.03.2025.
+        assert_eq!(vec![5, 6], find(date(3)));
+        assert_eq!(vec![5], find(date(4)));
+    }
+}
@@ -0,0 +1,400 @@
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    board::BoardEntry, hrdf::Hrdf, models::Journey, storage::DataStorage, utils::sub_1_day,
+};
+
+// ------------------------------------------------------------------------------------------------
+// --- TimetableSet
+// ------------------------------------------------------------------------------------------------
+
+/// Several timetable years (one DataStorage per HRDF export), e.g. the current and the next one around the December timetable change.
+/// The date-based queries are answered by the timetable whose period contains the date.
+/// If the periods overlap, the timetable starting last is used.
+#[derive(Debug)]
+pub struct TimetableSet {
+    timetables: Vec<(NaiveDate, NaiveDate, DataStorage)>, // (start_date, end_date, data_storage), sorted by start date.
+}
+
+impl TimetableSet {
+    pub fn new(data_storages: Vec<DataStorage>) -> Self {
+        let mut timetables = data_storages
+            .into_iter()
+            .map(|data_storage| {
+                let period = data_storage.timetable_period();
+                (period.start_date(), period.end_date(), data_storage)
+            })
+            .collect::<Vec<_>>();
+        timetables.sort_by_key(|&(start_date, _, _)| start_date);
+        Self { timetables }
+    }
+
+    pub fn from_hrdfs(hrdfs: Vec<Hrdf>) -> Self {
+        Self::new(hrdfs.into_iter().map(Hrdf::into_data_storage).collect())
+    }
+
+    // Getters/Setters
+
+    /// Returns the validity periods (start_date, end_date) of the timetables, sorted by start date.
+    pub fn periods(&self) -> Vec<(NaiveDate, NaiveDate)> {
+        self.timetables
+            .iter()
+            .map(|&(start_date, end_date, _)| (start_date, end_date))
+            .collect()
+    }
+
+    pub fn data_storages(&self) -> Vec<&DataStorage> {
+        self.timetables
+            .iter()
+            .map(|(_, _, data_storage)| data_storage)
+            .collect()
+    }
+
+    // Functions
+
+    /// Returns the timetable valid on the date, None if no timetable covers the date.
+    pub fn data_storage_for(&self, date: NaiveDate) -> Option<&DataStorage> {
+        self.timetables
+            .iter()
+            .rev()
+            .find(|&&(start_date, end_date, _)| start_date <= date && date <= end_date)
+            .map(|(_, _, data_storage)| data_storage)
+    }
+
+    /// Returns true if the journey identified by (legacy_id, administration) operates on the date.
+    /// The journeys are identified this way because the internal ids are specific to each timetable.
+    pub fn journey_operates_on(
+        &self,
+        legacy_id: i32,
+        administration: &str,
+        date: NaiveDate,
+    ) -> bool {
+        self.data_storage_for(date).is_some_and(|data_storage| {
+            data_storage
+                .find_journeys(None, None, Some(legacy_id), Some(date))
+                .iter()
+                .any(|journey| journey.administration() == administration)
+        })
+    }
+
+    /// See DataStorage::find_journeys. The date is mandatory to choose the timetable.
+    pub fn find_journeys(
+        &self,
+        transport_type_designation: Option<&str>,
+        line_designation: Option<&str>,
+        number: Option<i32>,
+        date: NaiveDate,
+    ) -> Vec<&Journey> {
+        self.data_storage_for(date)
+            .map(|data_storage| {
+                data_storage.find_journeys(
+                    transport_type_designation,
+                    line_designation,
+                    number,
+                    Some(date),
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// See DataStorage::departures. Each journey is taken from the timetable valid on its service date,
+    /// so the window can span a change of timetable.
+    pub fn departures(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        window: TimeDelta,
+    ) -> Vec<BoardEntry<'_>> {
+        self.board(from, window, |data_storage| {
+            data_storage.departures(stop_id, from, window)
+        })
+    }
+
+    /// See DataStorage::arrivals. Each journey is taken from the timetable valid on its service date,
+    /// so the window can span a change of timetable.
+    pub fn arrivals(
+        &self,
+        stop_id: i32,
+        from: NaiveDateTime,
+        window: TimeDelta,
+    ) -> Vec<BoardEntry<'_>> {
+        self.board(from, window, |data_storage| {
+            data_storage.arrivals(stop_id, from, window)
+        })
+    }
+
+    /// Returns the stops and lines which appear or disappear at each change of timetable.
+    pub fn changes(&self) -> Vec<TimetableChange> {
+        self.timetables
+            .windows(2)
+            .map(|timetables| {
+                let (_, _, previous) = &timetables[0];
+                let (date, _, next) = &timetables[1];
+
+                let (previous_stop_ids, next_stop_ids) =
+                    (served_stop_ids(previous), served_stop_ids(next));
+                let (previous_lines, next_lines) = (lines(previous), lines(next));
+
+                TimetableChange {
+                    date: *date,
+                    added_stop_ids: sorted_difference(&next_stop_ids, &previous_stop_ids),
+                    removed_stop_ids: sorted_difference(&previous_stop_ids, &next_stop_ids),
+                    added_lines: sorted_difference(&next_lines, &previous_lines),
+                    removed_lines: sorted_difference(&previous_lines, &next_lines),
+                }
+            })
+            .collect()
+    }
+
+    /// Merges the boards of the timetables covering the window, the previous service day included.
+    fn board<'a>(
+        &'a self,
+        from: NaiveDateTime,
+        window: TimeDelta,
+        create_board: impl Fn(&'a DataStorage) -> Vec<BoardEntry<'a>>,
+    ) -> Vec<BoardEntry<'a>> {
+        let (first_date, last_date) = (sub_1_day(from.date()), (from + window).date());
+        let mut entries: Vec<_> = self
+            .timetables
+            .iter()
+            .filter(|&&(start_date, end_date, _)| start_date <= last_date && first_date <= end_date)
+            .flat_map(|(_, _, data_storage)| {
+                create_board(data_storage)
+                    .into_iter()
+                    // If the periods overlap, the service date is only answered by one timetable.
+                    .filter(move |entry| {
+                        self.data_storage_for(entry.service_date())
+                            .is_some_and(|valid| std::ptr::eq(valid, data_storage))
+                    })
+            })
+            .collect();
+        // The sort is stable, the entries of the same time stay in the order of the timetables.
+        entries.sort_by_key(|entry| entry.time());
+        entries
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- TimetableChange
+// ------------------------------------------------------------------------------------------------
+
+/// Differences between two consecutive timetables. The lines are (transport type designation, line designation) pairs, e.g. ("IC", "1").
+#[derive(Debug)]
+pub struct TimetableChange {
+    date: NaiveDate, // Start date of the new timetable.
+    added_stop_ids: Vec<i32>,
+    removed_stop_ids: Vec<i32>,
+    added_lines: Vec<(String, String)>,
+    removed_lines: Vec<(String, String)>,
+}
+
+impl TimetableChange {
+    // Getters/Setters
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn added_stop_ids(&self) -> &Vec<i32> {
+        &self.added_stop_ids
+    }
+
+    pub fn removed_stop_ids(&self) -> &Vec<i32> {
+        &self.removed_stop_ids
+    }
+
+    pub fn added_lines(&self) -> &Vec<(String, String)> {
+        &self.added_lines
+    }
+
+    pub fn removed_lines(&self) -> &Vec<(String, String)> {
+        &self.removed_lines
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+/// The stops served by at least one journey.
+fn served_stop_ids(data_storage: &DataStorage) -> FxHashSet<i32> {
+    data_storage
+        .bit_fields_by_stop_id()
+        .keys()
+        .copied()
+        .collect()
+}
+
+fn lines(data_storage: &DataStorage) -> FxHashSet<(String, String)> {
+    data_storage
+        .journeys()
+        .entries()
+        .into_iter()
+        .filter_map(|journey| {
+            let line_designation = journey.line_designation(data_storage)?;
+            let transport_type_designation = journey.transport_type(data_storage).designation();
+            Some((
+                transport_type_designation.to_string(),
+                line_designation.to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn sorted_difference<T: Clone + Eq + Ord + std::hash::Hash>(
+    a: &FxHashSet<T>,
+    b: &FxHashSet<T>,
+) -> Vec<T> {
+    let mut result: Vec<_> = a.difference(b).cloned().collect();
+    result.sort();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::data_storage_from;
+    use pretty_assertions::assert_eq;
+
+    /// - Timetable 2025: IC 1 (line 1) A 23:50, B 00:20
+    /// - Timetable 2026: IC 2 (line 2) A 00:10, B 00:30, C 00:40
+    fn timetable_set() -> TimetableSet {
+        let timetable_2025 = data_storage_from(&[
+            ("BAHNHOF", &["8500010     A$<1>", "8500020     B$<1>"]),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000001 000011                                           %",
+                    "*G IC  8500010 8500020                                     %",
+                    "*A VE 8500010 8500020 000000                               %",
+                    "*L 1        8500010 8500020                                %",
+                    "8500010 A                            02350                 %",
+                    "8500020 B                     02420                        %",
+                ],
+            ),
+        ]);
+        let timetable_2026 = data_storage_from(&[
+            (
+                "BAHNHOF",
+                &[
+                    "8500010     A$<1>",
+                    "8500020     B$<1>",
+                    "8500030     C$<1>",
+                ],
+            ),
+            (
+                "ECKDATEN",
+                &[
+                    "14.12.2025",
+                    "12.12.2026",
+                    "Fahrplan 2026$01.12.2025 15:23:54$5.40.41$INFO+",
+                ],
+            ),
+            (
+                "FPLAN",
+                &[
+                    "*Z 000002 000011                                           %",
+                    "*G IC  8500010 8500030                                     %",
+                    "*A VE 8500010 8500030 000000                               %",
+                    "*L 2        8500010 8500030                                %",
+                    "8500010 A                            00010                 %",
+                    "8500020 B                     00030  00030                 %",
+                    "8500030 C                     00040                        %",
+                ],
+            ),
+        ]);
+        TimetableSet::new(vec![timetable_2026, timetable_2025])
+    }
+
+    fn summary(entries: &[BoardEntry]) -> Vec<(i32, String, NaiveDate)> {
+        entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.journey().legacy_id(),
+                    entry.time().format("%Y-%m-%d %H:%M").to_string(),
+                    entry.service_date(),
+                )
+            })
+            .collect()
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn data_storage_for_v207() {
+        let timetable_set = timetable_set();
+
+        assert_eq!(
+            vec![
+                (date("2024-12-15"), date("2025-12-13")),
+                (date("2025-12-14"), date("2026-12-12")),
+            ],
+            timetable_set.periods()
+        );
+        assert!(timetable_set.journey_operates_on(1, "000011", date("2025-12-13")));
+        assert!(!timetable_set.journey_operates_on(1, "000011", date("2025-12-14")));
+        assert!(timetable_set.journey_operates_on(2, "000011", date("2025-12-14")));
+        assert!(timetable_set.data_storage_for(date("2026-12-13")).is_none());
+    }
+
+    #[test]
+    fn departures_across_timetable_change_v207() {
+        let timetable_set = timetable_set();
+        let from = date("2025-12-13").and_hms_opt(23, 0, 0).unwrap();
+
+        let departures = timetable_set.departures(8500010, from, TimeDelta::hours(2));
+        assert_eq!(
+            vec![
+                (1, "2025-12-13 23:50".to_string(), date("2025-12-13")),
+                (2, "2025-12-14 00:10".to_string(), date("2025-12-14")),
+            ],
+            summary(&departures)
+        );
+    }
+
+    #[test]
+    fn arrivals_after_midnight_from_previous_timetable_v207() {
+        let timetable_set = timetable_set();
+        let from = date("2025-12-14").and_hms_opt(0, 0, 0).unwrap();
+
+        // The journey 1 of the last day of the timetable 2025 arrives in the timetable 2026.
+        let arrivals = timetable_set.arrivals(8500020, from, TimeDelta::hours(1));
+        assert_eq!(
+            vec![
+                (1, "2025-12-14 00:20".to_string(), date("2025-12-13")),
+                (2, "2025-12-14 00:30".to_string(), date("2025-12-14")),
+            ],
+            summary(&arrivals)
+        );
+
+        // The next day, only the timetable 2026 applies.
+        let from = date("2025-12-15").and_hms_opt(0, 0, 0).unwrap();
+        let arrivals = timetable_set.arrivals(8500020, from, TimeDelta::hours(1));
+        assert_eq!(
+            vec![(2, "2025-12-15 00:30".to_string(), date("2025-12-15"))],
+            summary(&arrivals)
+        );
+    }
+
+    #[test]
+    fn changes_v207() {
+        let changes = timetable_set().changes();
+
+        assert_eq!(1, changes.len());
+        assert_eq!(date("2025-12-14"), changes[0].date());
+        assert_eq!(&vec![8500030], changes[0].added_stop_ids());
+        assert!(changes[0].removed_stop_ids().is_empty());
+        assert_eq!(
+            &vec![("IC".to_string(), "2".to_string())],
+            changes[0].added_lines()
+        );
+        assert_eq!(
+            &vec![("IC".to_string(), "1".to_string())],
+            changes[0].removed_lines()
+        );
+    }
+}
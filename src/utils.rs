@@ -2,11 +2,17 @@
 // --- AutoIncrement
 // ------------------------------------------------------------------------------------------------
 
-use std::cell::RefCell;
+use std::{cell::RefCell, error::Error};
 
-use chrono::{Days, NaiveDate, NaiveTime};
+use chrono::{
+    DateTime, Days, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone,
+};
+use chrono_tz::Tz;
 
-use crate::{models::TimetableMetadataEntry, storage::ResourceStorage};
+use crate::{
+    models::{TimetableInfo, TimetableMetadataEntry, TimetablePeriod},
+    storage::ResourceStorage,
+};
 
 pub struct AutoIncrement {
     value: RefCell<i32>,
@@ -49,26 +55,85 @@ pub fn create_time_from_value(value: u32) -> NaiveTime {
     create_time(value / 100, value % 100)
 }
 
+/// Converts a local date-time to the timezone.
+/// In the repeated hour (end of daylight saving time), the earliest date-time which is not before not_before is chosen.
+/// In the skipped hour (start of daylight saving time), the first date-time after the gap is chosen,
+/// e.g. 02:30 becomes 03:00 in Europe/Zurich, so that the date-times along a route never go backwards.
+/// It is never before not_before either.
+pub fn to_timezone(
+    date_time: NaiveDateTime,
+    timezone: Tz,
+    not_before: Option<DateTime<Tz>>,
+) -> DateTime<Tz> {
+    match timezone.from_local_datetime(&date_time) {
+        LocalResult::Single(result) => result,
+        LocalResult::Ambiguous(earliest, latest) => match not_before {
+            Some(not_before) if earliest < not_before => latest,
+            _ => earliest,
+        },
+        LocalResult::None => {
+            // The transitions happen on a whole minute, the first valid minute is the end of the gap.
+            let mut end_of_gap = date_time;
+            let result = loop {
+                end_of_gap += TimeDelta::minutes(1);
+                if let Some(result) = timezone.from_local_datetime(&end_of_gap).earliest() {
+                    break result;
+                }
+            };
+            match not_before {
+                Some(not_before) if result < not_before => not_before,
+                _ => result,
+            }
+        }
+    }
+}
+
 pub fn timetable_start_date(
     timetable_metadata: &ResourceStorage<TimetableMetadataEntry>,
-) -> Result<NaiveDate, &str> {
-    let result = timetable_metadata
-        .data()
-        .values()
-        .find(|val| val.key() == "start_date")
-        .ok_or("Key \"start_date\" missing.")?
-        .value_as_NaiveDate();
-    Ok(result)
+) -> Result<NaiveDate, Box<dyn Error>> {
+    let value = timetable_metadata_value(timetable_metadata, "start_date")?;
+    Ok(NaiveDate::parse_from_str(value, "%Y-%m-%d")?)
 }
 
 pub fn timetable_end_date(
     timetable_metadata: &ResourceStorage<TimetableMetadataEntry>,
-) -> Result<NaiveDate, &str> {
-    let result = timetable_metadata
+) -> Result<NaiveDate, Box<dyn Error>> {
+    let value = timetable_metadata_value(timetable_metadata, "end_date")?;
+    Ok(NaiveDate::parse_from_str(value, "%Y-%m-%d")?)
+}
+
+/// Builds the typed ECKDATEN data. The start and end dates are required, the other values default to an empty string.
+pub fn timetable_info(
+    timetable_metadata: &ResourceStorage<TimetableMetadataEntry>,
+) -> Result<TimetableInfo, Box<dyn Error>> {
+    let period = TimetablePeriod::new(
+        timetable_start_date(timetable_metadata)?,
+        timetable_end_date(timetable_metadata)?,
+    )?;
+    let value = |key| {
+        timetable_metadata_value(timetable_metadata, key)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let created_at = NaiveDateTime::parse_from_str(&value("created_at"), "%d.%m.%Y %H:%M:%S").ok();
+
+    Ok(TimetableInfo::new(
+        period,
+        value("name"),
+        created_at,
+        value("version"),
+        value("provider"),
+    ))
+}
+
+fn timetable_metadata_value<'a>(
+    timetable_metadata: &'a ResourceStorage<TimetableMetadataEntry>,
+    key: &str,
+) -> Result<&'a str, Box<dyn Error>> {
+    let entry = timetable_metadata
         .data()
         .values()
-        .find(|val| val.key() == "end_date")
-        .expect("Key \"end_date\" missing.")
-        .value_as_NaiveDate();
-    Ok(result)
+        .find(|entry| entry.key() == key)
+        .ok_or_else(|| format!("Key \"{key}\" missing."))?;
+    Ok(entry.value())
 }
@@ -0,0 +1,300 @@
+// ------------------------------------------------------------------------------------------------
+// --- Vehicle runs (through services)
+// ------------------------------------------------------------------------------------------------
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    models::{Journey, Model},
+    storage::DataStorage,
+    utils::add_1_day,
+};
+
+/// A journey into which the passengers of the previous journey can stay on board (DURCHBI file).
+#[derive(Clone, Copy, Debug)]
+pub struct ThroughJourney<'a> {
+    journey: &'a Journey,
+    service_date: NaiveDate,
+    from_stop_id: i32,      // Stop where the passengers leave the previous journey.
+    departure_index: usize, // Position of the stop where the passengers get on the journey in its route.
+}
+
+impl<'a> ThroughJourney<'a> {
+    // Getters/Setters
+
+    pub fn journey(&self) -> &'a Journey {
+        self.journey
+    }
+
+    pub fn service_date(&self) -> NaiveDate {
+        self.service_date
+    }
+
+    pub fn from_stop_id(&self) -> i32 {
+        self.from_stop_id
+    }
+
+    pub fn departure_index(&self) -> usize {
+        self.departure_index
+    }
+}
+
+/// Journeys operated one after the other by the same vehicle. The passengers can stay on board from the first to the last one.
+#[derive(Debug)]
+pub struct VehicleRun<'a> {
+    journeys: Vec<(&'a Journey, NaiveDate)>, // (journey, service_date), in running order.
+}
+
+impl<'a> VehicleRun<'a> {
+    // Getters/Setters
+
+    pub fn journeys(&self) -> &Vec<(&'a Journey, NaiveDate)> {
+        &self.journeys
+    }
+
+    // Functions
+
+    /// Returns the stops served by the run, in order. The stop joining two journeys appears only once.
+    pub fn stop_ids(&self) -> Vec<i32> {
+        let mut stop_ids: Vec<i32> = Vec::new();
+        for (journey, _) in &self.journeys {
+            let route = journey.route();
+            let skip = usize::from(stop_ids.last() == Some(&journey.first_stop_id()));
+            stop_ids.extend(
+                route
+                    .iter()
+                    .skip(skip)
+                    .map(|route_entry| route_entry.stop_id()),
+            );
+        }
+        stop_ids
+    }
+
+    pub fn first_stop_id(&self) -> i32 {
+        // unwrap: A run always contains at least 1 journey.
+        self.journeys.first().unwrap().0.first_stop_id()
+    }
+
+    pub fn last_stop_id(&self) -> i32 {
+        // unwrap: A run always contains at least 1 journey.
+        self.journeys.last().unwrap().0.last_stop_id()
+    }
+}
+
+impl DataStorage {
+    /// Returns the journeys the passengers of the journey can stay on board into, according to the through services
+    /// valid on the service date. The journeys starting after midnight are on the next service date.
+    pub fn through_journeys(
+        &self,
+        journey: &Journey,
+        service_date: NaiveDate,
+    ) -> Vec<ThroughJourney<'_>> {
+        let mut through_services = self
+            .through_services_by_journey_id()
+            .get(&journey.id())
+            .cloned()
+            .unwrap_or_default();
+        through_services.sort_unstable();
+
+        let mut through_journeys = Vec::new();
+        for (through_service_id, next_journey_id) in through_services {
+            // unwrap: The through services and the journeys of the index always exist.
+            let through_service = self.through_service().find(through_service_id).unwrap();
+            let next_journey = self.journeys().find(next_journey_id).unwrap();
+            if !self
+                .bit_fields_by_day()
+                .contains(service_date, through_service.bit_field_id())
+            {
+                continue;
+            }
+
+            let from_stop_id = through_service.journey_1_stop_id();
+            let Some(arrival_time) = journey
+                .route()
+                .iter()
+                .rposition(|route_entry| route_entry.stop_id() == from_stop_id)
+                .and_then(|index| time_at(journey, service_date, index))
+            else {
+                continue;
+            };
+            // unwrap: The journey 2 of the index always serves the stop.
+            let departure_index = next_journey
+                .route()
+                .iter()
+                .position(|route_entry| {
+                    route_entry.stop_id() == through_service.journey_2_stop_id()
+                })
+                .unwrap();
+
+            let next_service_date =
+                [service_date, add_1_day(service_date)]
+                    .into_iter()
+                    .find(|&next_service_date| {
+                        time_at(next_journey, next_service_date, departure_index)
+                            .is_some_and(|departure_time| departure_time >= arrival_time)
+                            && next_journey.operates_on(next_service_date, self)
+                    });
+            if let Some(next_service_date) = next_service_date {
+                through_journeys.push(ThroughJourney {
+                    journey: next_journey,
+                    service_date: next_service_date,
+                    from_stop_id,
+                    departure_index,
+                });
+            }
+        }
+        through_journeys
+    }
+
+    /// Returns the run starting with the journey. If a journey continues into several journeys (the vehicle is split),
+    /// the first one is followed.
+    pub fn vehicle_run<'a>(
+        &'a self,
+        journey: &'a Journey,
+        service_date: NaiveDate,
+    ) -> VehicleRun<'a> {
+        let mut journeys = vec![(journey, service_date)];
+        let mut visited = FxHashSet::default();
+        visited.insert((journey.id(), service_date));
+
+        while let Some(&(journey, service_date)) = journeys.last() {
+            let Some(through_journey) = self
+                .through_journeys(journey, service_date)
+                .into_iter()
+                .next()
+            else {
+                break;
+            };
+            if !visited.insert((through_journey.journey.id(), through_journey.service_date)) {
+                break;
+            }
+            journeys.push((through_journey.journey, through_journey.service_date));
+        }
+
+        VehicleRun { journeys }
+    }
+
+    /// Returns the runs made of at least 2 journeys and starting on the date, sorted by the id of their first journey.
+    /// A journey joined by several others (the vehicles are coupled) belongs to each of their runs.
+    pub fn vehicle_runs(&self, date: NaiveDate) -> Vec<VehicleRun<'_>> {
+        let mut successors: FxHashMap<i32, Vec<ThroughJourney<'_>>> = FxHashMap::default();
+        for &journey_id in self.through_services_by_journey_id().keys() {
+            // unwrap: The journeys of the index always exist.
+            let journey = self.journeys().find(journey_id).unwrap();
+            if !journey.operates_on(date, self) {
+                continue;
+            }
+            let through_journeys = self.through_journeys(journey, date);
+            if !through_journeys.is_empty() {
+                successors.insert(journey_id, through_journeys);
+            }
+        }
+
+        let has_predecessor: FxHashSet<_> = successors
+            .values()
+            .flatten()
+            .filter(|through_journey| through_journey.service_date == date)
+            .map(|through_journey| through_journey.journey.id())
+            .collect();
+
+        let mut first_journey_ids: Vec<_> = successors
+            .keys()
+            .copied()
+            .filter(|journey_id| !has_predecessor.contains(journey_id))
+            .collect();
+        first_journey_ids.sort_unstable();
+
+        first_journey_ids
+            .into_iter()
+            // unwrap: The journeys of the index always exist.
+            .map(|journey_id| self.vehicle_run(self.journeys().find(journey_id).unwrap(), date))
+            .collect()
+    }
+}
+
+// ------------------------------------------------------------------------------------------------
+// --- Helper Functions
+// ------------------------------------------------------------------------------------------------
+
+/// Time of the journey at the route entry on the service date, departure time if there is one, arrival time otherwise.
+fn time_at(journey: &Journey, service_date: NaiveDate, index: usize) -> Option<NaiveDateTime> {
+    let (arrival_offset, departure_offset) = journey.stop_offsets()[index];
+    let offset = departure_offset.or(arrival_offset)?;
+    // unwrap: The first route entry always has a departure time.
+    let start = NaiveDateTime::new(service_date, journey.route()[0].departure_time().unwrap());
+    Some(start + TimeDelta::minutes(offset.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::data_storage_from;
+    use pretty_assertions::assert_eq;
+
+    /// - 1234 (801, region 1): A 08:00, H 08:30
+    /// - 1234 (801, region 2): H 09:00, B 09:30
+    /// - 5: A 07:50, H 08:20
+    /// - 6: H 08:35, D 09:00
+    #[test]
+    fn colliding_journeys_v207() {
+        let data_storage = data_storage_from(&[
+            (
+                "FPLAN",
+                &[
+                    "*Z 001234 000801                                           %",
+                    "*G B   8500010 8500080                                     %",
+                    "*A VE 8500010 8500080 000000                               %",
+                    "*I RN                        000000001                     %",
+                    "8500010 A                            00800                 %",
+                    "8500080 H                     00830                        %",
+                    "*Z 001234 000801                                           %",
+                    "*G B   8500080 8500020                                     %",
+                    "*A VE 8500080 8500020 000000                               %",
+                    "*I RN                        000000002                     %",
+                    "8500080 H                            00900                 %",
+                    "8500020 B                     00930                        %",
+                    "*Z 000005 000011                                           %",
+                    "*G B   8500010 8500080                                     %",
+                    "*A VE 8500010 8500080 000000                               %",
+                    "8500010 A                            00750                 %",
+                    "8500080 H                     00820                        %",
+                    "*Z 000006 000011                                           %",
+                    "*G B   8500080 8500040                                     %",
+                    "*A VE 8500080 8500040 000000                               %",
+                    "8500080 H                            00835                 %",
+                    "8500040 D                     00900                        %",
+                ],
+            ),
+            (
+                "DURCHBI",
+                &[
+                    "001234 000801 8500080 000006 000011 000000 8500080",
+                    "000005 000011 8500080 001234 000801 000000 8500080",
+                    "001234 000801 8500040 000006 000011 000000 8500080",
+                ],
+            ),
+        ]);
+        let date = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let run_of = |journey_id| -> Vec<i32> {
+            let journey = data_storage.journeys().find(journey_id).unwrap();
+            data_storage
+                .vehicle_run(journey, date)
+                .journeys()
+                .iter()
+                .map(|(journey, _)| journey.id())
+                .collect()
+        };
+
+        // Both journeys 1234 serve H: the one of the region 1 ends there, the one of the region 2 starts there.
+        assert_eq!(vec![1, 4], run_of(1));
+        assert_eq!(vec![3, 2], run_of(3));
+        assert_eq!(vec![2], run_of(2));
+        // Neither journey 1234 serves D, the last through service is skipped.
+        assert_eq!(
+            vec![(1, 4)],
+            data_storage.through_services_by_journey_id()[&1]
+        );
+        assert_eq!(2, data_storage.through_services_by_journey_id().len());
+    }
+}